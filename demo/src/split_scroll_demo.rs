@@ -1,4 +1,4 @@
-use egui::{Rect, Ui, Vec2b, pos2, vec2};
+use egui::{Rect, Ui, pos2, vec2};
 
 use egui_table::{SplitScroll, SplitScrollDelegate};
 
@@ -10,13 +10,11 @@ impl SplitScrollDemo {
         _ = self;
         let mut delegate = DemoScrollDelegate {};
 
-        SplitScroll {
-            scroll_enabled: Vec2b::new(true, true),
-            fixed_size: vec2(123.0, 37.0),
-            scroll_outer_size: vec2(600.0, 400.0),
-            scroll_content_size: vec2(10_000.0, 10_000.0),
-            stick_to_bottom: false,
-        }
+        SplitScroll::new(
+            vec2(123.0, 37.0),
+            vec2(600.0, 400.0),
+            vec2(10_000.0, 10_000.0),
+        )
         .show(ui, &mut delegate);
     }
 }
@@ -1,17 +1,45 @@
-use std::collections::BTreeMap;
+use std::{collections::BTreeMap, sync::Arc};
 
 use egui::{Align2, Context, Id, Margin, NumExt as _, Sense, Ui, Vec2};
 
+/// Number of rows in each section, when showing sections.
+const SECTION_SIZE: u64 = 50;
+
 #[derive(serde::Deserialize, serde::Serialize)]
+#[serde(default)]
 pub struct TableDemo {
     num_columns: usize,
     num_rows: u64,
     num_sticky_cols: usize,
+    num_sticky_cols_right: usize,
     default_column: egui_table::Column,
     auto_size_mode: egui_table::AutoSizeMode,
+    selection_mode: egui_table::SelectionMode,
+    copy_format: egui_table::TextFormat,
     top_row_height: f32,
     row_height: f32,
+    auto_row_heights: bool,
+    show_footer: bool,
+    show_pinned_rows_in_body: bool,
+    show_sections: bool,
+    show_tree: bool,
+    show_merged_cells: bool,
+    show_filter_row: bool,
+    /// Keyed by source row, so that the details stay with their row when sorting.
     is_row_expanded: BTreeMap<u64, bool>,
+    sort: egui_table::SortState,
+    filters: egui_table::FilterState,
+
+    /// Values the user pasted into the cells or edited, keyed by source row and column.
+    #[serde(skip)]
+    edited_values: BTreeMap<(u64, usize), String>,
+
+    #[serde(skip)]
+    row_mapping: Arc<egui_table::RowMapping>,
+
+    /// The [`Self::num_rows`] that [`Self::row_mapping`] was made for.
+    #[serde(skip)]
+    row_mapping_num_rows: Option<u64>,
     prefetched: Vec<egui_table::PrefetchInfo>,
 }
 
@@ -21,13 +49,31 @@ impl Default for TableDemo {
             num_columns: 20,
             num_rows: 10_000,
             num_sticky_cols: 1,
+            num_sticky_cols_right: 0,
             default_column: egui_table::Column::new(100.0)
                 .range(10.0..=500.0)
-                .resizable(true),
+                .resizable(true)
+                .reorderable(true)
+                .sortable(true)
+                .editable(true),
             auto_size_mode: egui_table::AutoSizeMode::default(),
+            selection_mode: egui_table::SelectionMode::Range,
+            copy_format: egui_table::TextFormat::default(),
             top_row_height: 24.0,
             row_height: 18.0,
+            auto_row_heights: false,
+            show_footer: false,
+            show_pinned_rows_in_body: true,
+            show_sections: false,
+            show_tree: false,
+            show_merged_cells: false,
+            show_filter_row: false,
             is_row_expanded: Default::default(),
+            sort: Default::default(),
+            filters: Default::default(),
+            edited_values: Default::default(),
+            row_mapping: Default::default(),
+            row_mapping_num_rows: None,
             prefetched: vec![],
         }
     }
@@ -35,12 +81,58 @@ impl Default for TableDemo {
 
 impl TableDemo {
     fn was_row_prefetched(&self, row_nr: u64) -> bool {
-        self.prefetched
-            .iter()
-            .any(|info| info.visible_rows.contains(&row_nr))
+        self.prefetched.iter().any(|info| {
+            info.visible_rows.contains(&row_nr)
+                || info.pinned_rows.contains(&row_nr)
+                || info.sticky_section_header == Some(row_nr)
+                || info.editing_cell.is_some_and(|cell| cell.row_nr == row_nr)
+        })
+    }
+
+    /// Our data is the same in all columns, so sorting by any column sorts by row number.
+    fn update_row_mapping(&mut self) {
+        // We don't set any column ids, so the column index is used:
+        let filters: Vec<(usize, &egui_table::FilterValue)> = (0..self.num_columns)
+            .filter_map(|col_nr| Some((col_nr, self.filters.get(Id::new(col_nr))?)))
+            .collect();
+        let mut row_mapping = egui_table::RowMapping::filtered(self.num_rows, |source_row| {
+            filters
+                .iter()
+                .all(|(col_nr, value)| value.matches(&self.filter_text(source_row, *col_nr)))
+        });
+        if let Some(key) = self.sort.keys.first()
+            && !key.direction.is_ascending()
+        {
+            row_mapping.reverse();
+        }
+        self.row_mapping = Arc::new(row_mapping);
+        self.row_mapping_num_rows = Some(self.num_rows);
+    }
+
+    /// What the filter of a column looks at, see [`egui_table::TableDelegate::column_filter`].
+    fn filter_text(&self, source_row: u64, col_nr: usize) -> String {
+        if col_nr == 1 {
+            if source_row.is_multiple_of(2) {
+                "Even".to_owned()
+            } else {
+                "Odd".to_owned()
+            }
+        } else {
+            self.cell_value(source_row, col_nr)
+        }
+    }
+
+    fn cell_value(&self, source_row: u64, col_nr: usize) -> String {
+        if let Some(value) = self.edited_values.get(&(source_row, col_nr)) {
+            value.clone()
+        } else if col_nr == 0 {
+            source_row.to_string()
+        } else {
+            format!("({source_row}, {col_nr})")
+        }
     }
 
-    fn cell_content_ui(&mut self, row_nr: u64, col_nr: usize, ui: &mut egui::Ui) {
+    fn cell_content_ui(&mut self, row_nr: u64, source_row: u64, col_nr: usize, ui: &mut egui::Ui) {
         assert!(
             self.was_row_prefetched(row_nr),
             "Was asked to show row {row_nr} which was not prefetched! This is a bug in egui_table."
@@ -48,10 +140,10 @@ impl TableDemo {
 
         let is_expanded = self
             .is_row_expanded
-            .get(&row_nr)
+            .get(&source_row)
             .copied()
             .unwrap_or_default();
-        let expandedness = ui.ctx().animate_bool(Id::new(row_nr), is_expanded);
+        let expandedness = ui.ctx().animate_bool(Id::new(source_row), is_expanded);
 
         ui.vertical(|ui| {
             if col_nr == 0 {
@@ -62,28 +154,26 @@ impl TableDemo {
                     if response.clicked() {
                         // Toggle.
                         // Note: we use a map instead of a set so that we can animate opening and closing of each column.
-                        self.is_row_expanded.insert(row_nr, !is_expanded);
+                        self.is_row_expanded.insert(source_row, !is_expanded);
                     }
 
-                    ui.label(row_nr.to_string());
+                    ui.label(self.cell_value(source_row, col_nr));
                 });
             } else {
                 ui.horizontal(|ui| {
-                    ui.label(format!("({row_nr}, {col_nr})"));
+                    ui.label(self.cell_value(source_row, col_nr));
 
-                    if (row_nr + col_nr as u64).is_multiple_of(27) {
-                        if !ui.is_sizing_pass() {
+                    if (source_row + col_nr as u64).is_multiple_of(27) {
+                        if self.auto_row_heights {
+                            // The row grows to fit all of it:
+                            ui.style_mut().wrap_mode = Some(egui::TextWrapMode::Wrap);
+                        } else if !ui.is_sizing_pass() {
                             // During a sizing pass we don't truncate!
                             ui.style_mut().wrap_mode = Some(egui::TextWrapMode::Truncate);
                         }
                         ui.label("Extra long cell that will be truncated with an ellipsis character because it is so long");
                     }
                 });
-
-                if 0.0 < expandedness {
-                    ui.label("Expanded content");
-                    ui.label("Blah blah blah…");
-                }
             }
         });
     }
@@ -163,6 +253,83 @@ impl egui_table::TableDelegate for TableDemo {
             });
     }
 
+    fn footer_cell_ui(&mut self, ui: &mut egui::Ui, cell_info: &egui_table::FooterCellInfo) {
+        egui::Frame::NONE
+            .inner_margin(Margin::symmetric(4, 0))
+            .show(ui, |ui| {
+                if cell_info.col_range.start == 0 {
+                    ui.strong(format!("{} rows", self.num_rows));
+                } else {
+                    // The sum of all the row numbers:
+                    let sum = self.num_rows * self.num_rows.saturating_sub(1) / 2;
+                    ui.strong(format!("Σ {sum}"));
+                }
+            });
+    }
+
+    fn cell_text(&self, source_row: u64, col_nr: usize) -> Option<String> {
+        Some(self.cell_value(source_row, col_nr))
+    }
+
+    fn commit_edit(&mut self, cell: &egui_table::CellInfo, value: String) -> Result<(), String> {
+        if cell.col_nr == 0 && value.parse::<u64>().is_err() {
+            return Err("The first column only takes whole numbers".to_owned());
+        }
+        self.edited_values
+            .insert((cell.source_row, cell.col_nr), value);
+        Ok(())
+    }
+
+    fn paste(&mut self, start_cell: egui_table::CellCoord, grid: Vec<Vec<String>>) {
+        let Some(column_order) = self.prefetched.last().map(|info| &info.column_order) else {
+            return;
+        };
+        for (row_nr, values) in (start_cell.row_nr..).zip(grid) {
+            let Some(source_row) = self.row_mapping.source_row(row_nr) else {
+                break; // Beyond the last row
+            };
+            let col_nrs = column_order.iter().skip(start_cell.col_pos);
+            for (&col_nr, value) in col_nrs.zip(values) {
+                self.edited_values.insert((source_row, col_nr), value);
+            }
+        }
+    }
+
+    fn header_cell_text(&self, cell: &egui_table::HeaderCellInfo) -> Option<String> {
+        if cell.row_nr == 0 {
+            (0 < cell.col_range.start).then(|| format!("This is group {}", cell.group_index))
+        } else {
+            Some(self.column_name(cell.group_index))
+        }
+    }
+
+    fn column_name(&self, col_nr: usize) -> String {
+        if col_nr == 0 {
+            "Row".to_owned()
+        } else {
+            format!("Column {col_nr}")
+        }
+    }
+
+    fn on_sort_changed(&mut self, sort: &egui_table::SortState) {
+        self.sort = sort.clone();
+        self.update_row_mapping();
+    }
+
+    fn column_filter(&self, col_nr: usize) -> Option<egui_table::ColumnFilter> {
+        use egui_table::ColumnFilter;
+        Some(match col_nr {
+            0 => ColumnFilter::NumberRange,
+            1 => ColumnFilter::Choice(vec!["Even".to_owned(), "Odd".to_owned()]),
+            _ => ColumnFilter::Text,
+        })
+    }
+
+    fn on_filters_changed(&mut self, filters: &egui_table::FilterState) {
+        self.filters = filters.clone();
+        self.update_row_mapping();
+    }
+
     // You can use row_ui to add some style or interaction to the entire row.
     fn row_ui(&mut self, ui: &mut Ui, _row_nr: u64) {
         if ui.rect_contains_pointer(ui.max_rect()) {
@@ -176,36 +343,128 @@ impl egui_table::TableDelegate for TableDemo {
     }
 
     fn cell_ui(&mut self, ui: &mut egui::Ui, cell_info: &egui_table::CellInfo) {
-        let egui_table::CellInfo { row_nr, col_nr, .. } = *cell_info;
+        let egui_table::CellInfo {
+            row_nr,
+            source_row,
+            col_nr,
+            ..
+        } = *cell_info;
 
         if row_nr % 2 == 1 {
             ui.painter()
                 .rect_filled(ui.max_rect(), 0.0, ui.visuals().faint_bg_color);
         }
 
+        if self.section_header_row(row_nr) == Some(row_nr) {
+            // This spans all the columns:
+            ui.painter()
+                .rect_filled(ui.max_rect(), 0.0, ui.visuals().extreme_bg_color);
+            egui::Frame::NONE
+                .inner_margin(Margin::symmetric(4, 0))
+                .show(ui, |ui| {
+                    ui.strong(format!("Section {}", row_nr / SECTION_SIZE));
+                });
+            return;
+        }
+
+        if 1 < cell_info.col_pos_range.len() {
+            egui::Frame::NONE
+                .inner_margin(Margin::symmetric(4, 0))
+                .show(ui, |ui| {
+                    ui.label(format!("Merged cell of row {source_row}"));
+                });
+            return;
+        }
+
+        let egui_table::CellInfo { row_range, .. } = cell_info;
+        if 1 < row_range.end - row_range.start {
+            egui::Frame::NONE
+                .inner_margin(Margin::symmetric(4, 0))
+                .show(ui, |ui| {
+                    ui.label(format!("Rows {} to {}", row_range.start, row_range.end - 1));
+                });
+            return;
+        }
+
         egui::Frame::NONE
             .inner_margin(Margin::symmetric(4, 0))
             .show(ui, |ui| {
-                self.cell_content_ui(row_nr, col_nr, ui);
+                self.cell_content_ui(row_nr, source_row, col_nr, ui);
             });
     }
 
-    fn row_top_offset(&self, ctx: &Context, _table_id: Id, row_nr: u64) -> f32 {
-        let fully_expanded_row_height = 48.0;
+    fn cell_span(&self, row_nr: u64, col_nr: usize) -> std::ops::Range<usize> {
+        if self.section_header_row(row_nr) == Some(row_nr) {
+            0..self.num_columns
+        } else if self.show_merged_cells && row_nr % 13 == 5 && (1..4).contains(&col_nr) {
+            1..4
+        } else {
+            col_nr..col_nr + 1
+        }
+    }
 
-        self.is_row_expanded
-            .range(0..row_nr)
-            .map(|(expanded_row_nr, expanded)| {
-                let how_expanded = ctx.animate_bool(Id::new(expanded_row_nr), *expanded);
-                how_expanded * fully_expanded_row_height
-            })
-            .sum::<f32>()
-            + row_nr as f32 * self.row_height
+    fn cell_row_span(&self, row_nr: u64, col_nr: usize) -> std::ops::Range<u64> {
+        let is_section_header = self.section_header_row(row_nr) == Some(row_nr);
+        if self.show_merged_cells && col_nr == 4 && !is_section_header {
+            let mut start = row_nr - row_nr % 5;
+            if self.section_header_row(start) == Some(start) {
+                start += 1; // Don't merge with the section header
+            }
+            start..row_nr - row_nr % 5 + 5
+        } else {
+            row_nr..row_nr + 1
+        }
+    }
+
+    fn section_header_row(&self, row_nr: u64) -> Option<u64> {
+        self.show_sections.then_some(row_nr - row_nr % SECTION_SIZE)
+    }
+
+    fn tree_node(&self, source_row: u64) -> egui_table::TreeNode {
+        // Every 100 rows form a tree: the root, with ten children, each with nine children of their own.
+        let num_rows_after = self.num_rows - source_row - 1;
+        let (depth, num_descendants) = if source_row.is_multiple_of(100) {
+            (0, 99)
+        } else if source_row.is_multiple_of(10) {
+            (1, 9)
+        } else {
+            (2, 0)
+        };
+        egui_table::TreeNode {
+            depth,
+            num_descendants: u64::min(num_descendants, num_rows_after),
+        }
+    }
+
+    fn default_row_height(&self) -> f32 {
+        self.row_height
+    }
+
+    fn row_detail_height(&self, ctx: &Context, _table_id: Id, row_nr: u64) -> f32 {
+        let fully_expanded_height = 48.0;
+        let source_row = self.row_mapping.source_row(row_nr).unwrap_or(row_nr);
+        let is_expanded = self
+            .is_row_expanded
+            .get(&source_row)
+            .copied()
+            .unwrap_or_default();
+        ctx.animate_bool(Id::new(source_row), is_expanded) * fully_expanded_height
+    }
+
+    fn row_detail_ui(&mut self, ui: &mut egui::Ui, row_nr: u64) {
+        egui::Frame::NONE
+            .inner_margin(Margin::symmetric(8, 4))
+            .show(ui, |ui| {
+                ui.label(format!(
+                    "Details of row {row_nr}, spanning the full width of the table"
+                ));
+                ui.label("Blah blah blah…");
+            });
     }
 }
 
 impl TableDemo {
-    pub fn ui(&mut self, ui: &mut egui::Ui) {
+    fn settings_ui(&mut self, ui: &mut egui::Ui) {
         egui::Grid::new("settings").show(ui, |ui| {
             ui.label("Columns");
             ui.add(egui::DragValue::new(&mut self.num_columns));
@@ -213,11 +472,23 @@ impl TableDemo {
 
             ui.label("Rows");
             let speed = 1.0 + 0.05 * self.num_rows as f32;
-            ui.add(
-                egui::DragValue::new(&mut self.num_rows)
-                    .speed(speed)
-                    .range(0..=10_000),
-            );
+            ui.horizontal(|ui| {
+                ui.add(
+                    egui::DragValue::new(&mut self.num_rows)
+                        .speed(speed)
+                        .range(0..=10_000),
+                );
+                if ui
+                    .button("Add 10")
+                    .on_hover_text(
+                        "Sort in descending order to add them at the top. \
+                        The rows in view stay put.",
+                    )
+                    .clicked()
+                {
+                    self.num_rows += 10;
+                }
+            });
             ui.end_row();
 
             ui.label("Height of top row");
@@ -228,10 +499,55 @@ impl TableDemo {
             ui.add(egui::DragValue::new(&mut self.row_height).range(0.0..=100.0));
             ui.end_row();
 
+            ui.label("Auto row heights");
+            ui.checkbox(&mut self.auto_row_heights, "Fit rows to their contents")
+                .on_hover_text("Long cells wrap instead of being truncated");
+            ui.end_row();
+
+            ui.label("Footer");
+            ui.checkbox(&mut self.show_footer, "Show totals");
+            ui.end_row();
+
+            ui.label("Pinned rows");
+            ui.checkbox(&mut self.show_pinned_rows_in_body, "Also show in body");
+            ui.end_row();
+
+            ui.label("Sections");
+            ui.checkbox(&mut self.show_sections, "Section header rows")
+                .on_hover_text(format!(
+                    "Every {SECTION_SIZE} rows starts with a sticky section header"
+                ));
+            ui.end_row();
+
+            ui.label("Tree");
+            ui.checkbox(&mut self.show_tree, "Show rows as a tree")
+                .on_hover_text("Every 100 rows form a tree, three levels deep");
+            ui.end_row();
+
+            ui.label("Merged cells");
+            ui.checkbox(&mut self.show_merged_cells, "Merge some cells")
+                .on_hover_text(
+                    "Every 13th row has a cell spanning columns 1 to 3, \
+                    and column 4 is merged for every 5 rows",
+                );
+            ui.end_row();
+
+            ui.label("Filters");
+            ui.checkbox(&mut self.show_filter_row, "Show filter row")
+                .on_hover_text(
+                    "The first column takes a range of row numbers, \
+                    the second even and/or odd rows, and the rest any text",
+                );
+            ui.end_row();
+
             ui.label("Sticky columns");
             ui.add(egui::DragValue::new(&mut self.num_sticky_cols));
             ui.end_row();
 
+            ui.label("Sticky columns on the right");
+            ui.add(egui::DragValue::new(&mut self.num_sticky_cols_right));
+            ui.end_row();
+
             ui.label("Default column width");
             ui.add(egui::DragValue::new(&mut self.default_column.current));
             ui.end_row();
@@ -264,11 +580,41 @@ impl TableDemo {
                 );
             });
             ui.end_row();
+
+            ui.label("Selection mode");
+            ui.horizontal(|ui| {
+                use egui_table::SelectionMode;
+                ui.radio_value(&mut self.selection_mode, SelectionMode::None, "None");
+                ui.radio_value(&mut self.selection_mode, SelectionMode::Single, "Single");
+                ui.radio_value(&mut self.selection_mode, SelectionMode::Multi, "Multi");
+                ui.radio_value(&mut self.selection_mode, SelectionMode::Range, "Range");
+                ui.radio_value(&mut self.selection_mode, SelectionMode::Cells, "Cells");
+            });
+            ui.end_row();
+
+            ui.label("Copy format").on_hover_text(
+                "Press Ctrl+C (Cmd+C on Mac) to copy the selection, \
+                and Ctrl+V to paste into the focused cell",
+            );
+            ui.horizontal(|ui| {
+                use egui_table::TextFormat;
+                ui.radio_value(&mut self.copy_format, TextFormat::Tsv, "TSV");
+                ui.radio_value(&mut self.copy_format, TextFormat::Csv, "CSV");
+                ui.radio_value(&mut self.copy_format, TextFormat::Markdown, "Markdown");
+                ui.radio_value(&mut self.copy_format, TextFormat::Html, "HTML");
+                ui.radio_value(&mut self.copy_format, TextFormat::JsonLines, "JSON Lines");
+            });
+            ui.end_row();
         });
+    }
+
+    pub fn ui(&mut self, ui: &mut egui::Ui) {
+        self.settings_ui(ui);
 
         let id_salt = Id::new("table_demo");
         let state_id = egui_table::Table::new().id_salt(id_salt).get_id(ui); // Note: must be here (in the correct outer `ui` scope) to be correct.
 
+        let mut export = false;
         ui.horizontal(|ui| {
             if ui.button("Reset settings").clicked() {
                 *self = Self::default();
@@ -280,10 +626,61 @@ impl TableDemo {
                 );
                 egui_table::TableState::reset(ui.ctx(), state_id);
             }
+            if !self.edited_values.is_empty() && ui.button("Clear edited values").clicked() {
+                self.edited_values.clear();
+            }
+            export = ui
+                .button("Export all rows")
+                .on_hover_text("Copy all rows to the clipboard, in the copy format")
+                .clicked();
         });
 
         ui.separator();
 
+        ui.horizontal(|ui| {
+            ui.label("Sorted by:");
+            if self.sort.is_empty() {
+                ui.weak("nothing (click a column header, shift-click to sort by more columns)");
+            }
+            for key in &self.sort.keys {
+                // We don't set any column ids, so the column index is used:
+                let Some(col_nr) =
+                    (0..self.num_columns).find(|&col_nr| Id::new(col_nr) == key.column_id)
+                else {
+                    continue;
+                };
+                let direction = if key.direction.is_ascending() {
+                    "⬆"
+                } else {
+                    "⬇"
+                };
+                ui.label(format!(
+                    "{} {direction}",
+                    egui_table::TableDelegate::column_name(self, col_nr)
+                ));
+            }
+        });
+
+        if !self.filters.is_empty() {
+            ui.horizontal(|ui| {
+                ui.label(format!(
+                    "Filtered: showing {} of {} rows",
+                    self.row_mapping.len(),
+                    self.num_rows
+                ));
+                if ui.button("Clear filters").clicked() {
+                    if let Some(mut state) = egui_table::TableState::load(ui.ctx(), state_id) {
+                        state.filters.clear();
+                        state.store(ui.ctx(), state_id);
+                    }
+                    self.filters.clear();
+                    self.update_row_mapping();
+                }
+            });
+        }
+
+        pinned_rows_ui(ui, state_id);
+
         ui.horizontal(|ui| {
             for info in &self.prefetched {
                 ui.label("Visible columns:");
@@ -302,7 +699,9 @@ impl TableDemo {
         });
 
         let first_scrollable_col = self.num_sticky_cols;
-        let last_col = self.num_columns.saturating_sub(1);
+        let last_col = self
+            .num_columns
+            .saturating_sub(1 + self.num_sticky_cols_right);
         let mid_col = usize::midpoint(first_scrollable_col, last_col);
         let mut scroll_to_column = None;
         ui.horizontal(|ui| {
@@ -341,11 +740,43 @@ impl TableDemo {
 
         ui.separator();
 
-        let mut table = egui_table::Table::new()
+        if self.row_mapping_num_rows != Some(self.num_rows) {
+            self.update_row_mapping();
+        }
+
+        let mut table = self.table(id_salt);
+        if let Some(scroll_to_column) = scroll_to_column {
+            table = table.scroll_to_column(scroll_to_column, None);
+        }
+        if let Some(scroll_to_row) = scroll_to_row {
+            table = table.scroll_to_row(scroll_to_row, None);
+        }
+
+        table.show(ui, self);
+
+        if export {
+            // Don't list the chunks of the export as prefetched:
+            let num_prefetched = self.prefetched.len();
+            match self
+                .table(id_salt)
+                .export(ui, vec![], self.copy_format, self)
+            {
+                Ok(bytes) => ui
+                    .ctx()
+                    .copy_text(String::from_utf8_lossy(&bytes).into_owned()),
+                Err(err) => log::error!("Failed to export the table: {err}"),
+            }
+            self.prefetched.truncate(num_prefetched);
+        }
+    }
+
+    fn table(&self, id_salt: Id) -> egui_table::Table {
+        let table = egui_table::Table::new()
             .id_salt(id_salt)
             .num_rows(self.num_rows)
             .columns(vec![self.default_column; self.num_columns])
             .num_sticky_cols(self.num_sticky_cols)
+            .num_sticky_cols_right(self.num_sticky_cols_right)
             .headers([
                 egui_table::HeaderRow {
                     height: self.top_row_height,
@@ -353,15 +784,64 @@ impl TableDemo {
                 },
                 egui_table::HeaderRow::new(self.top_row_height),
             ])
-            .auto_size_mode(self.auto_size_mode);
+            .footers(if self.show_footer {
+                vec![egui_table::FooterRow::new(self.top_row_height)]
+            } else {
+                vec![]
+            })
+            .auto_size_mode(self.auto_size_mode)
+            .selection_mode(self.selection_mode)
+            .keyboard_navigation(true)
+            .copy_format(self.copy_format)
+            .show_pinned_rows_in_body(self.show_pinned_rows_in_body)
+            .auto_row_heights(self.auto_row_heights)
+            // Also the collapsed ones, so they can animate closed:
+            .detail_rows(
+                self.is_row_expanded
+                    .keys()
+                    .filter_map(|&source_row| self.row_mapping.visual_row(source_row)),
+            )
+            .column_chooser(true);
+
+        let table = if self.show_filter_row {
+            table.filter_row(self.top_row_height)
+        } else {
+            table
+        };
+        if self.show_tree {
+            // The tree decides which rows are shown, so it isn't sorted or filtered:
+            table.tree_column(0)
+        } else {
+            table.row_mapping(Arc::clone(&self.row_mapping))
+        }
+    }
+}
 
-        if let Some(scroll_to_column) = scroll_to_column {
-            table = table.scroll_to_column(scroll_to_column, None);
+/// Pin and unpin rows by modifying the [`egui_table::TableState`] directly.
+fn pinned_rows_ui(ui: &mut Ui, state_id: Id) {
+    let Some(mut state) = egui_table::TableState::load(ui.ctx(), state_id) else {
+        return; // The table hasn't been shown yet
+    };
+
+    ui.horizontal(|ui| {
+        ui.label("Pinned rows:");
+        if state.pinned_rows.is_empty() {
+            ui.weak("none (select some rows and pin them)");
         }
-        if let Some(scroll_to_row) = scroll_to_row {
-            table = table.scroll_to_row(scroll_to_row, None);
+        for row_nr in &state.pinned_rows {
+            ui.label(row_nr.to_string());
         }
 
-        table.show(ui, self);
-    }
+        if ui.button("Pin selected rows").clicked() {
+            let selected_rows: Vec<u64> = state.selection.rows.iter().collect();
+            for row_nr in selected_rows {
+                state.pin_row(row_nr);
+            }
+            state.clone().store(ui.ctx(), state_id);
+        }
+        if ui.button("Unpin all").clicked() {
+            state.pinned_rows.clear();
+            state.clone().store(ui.ctx(), state_id);
+        }
+    });
 }
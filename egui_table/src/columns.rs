@@ -1,13 +1,19 @@
-//! Logic for constrained column auto-sizing.
+//! Logic for constrained column auto-sizing, and for column ordering.
 
-use egui::Rangef;
+use std::{collections::HashSet, ops::Range};
+
+use egui::{Id, Rangef};
 
 #[derive(Clone, Copy, Debug, serde::Deserialize, serde::Serialize)]
+#[serde(default)]
 pub struct Column {
     pub current: f32,
     pub range: Rangef,
     pub id: Option<egui::Id>,
     pub resizable: bool,
+    pub reorderable: bool,
+    pub sortable: bool,
+    pub editable: bool,
     pub auto_size_this_frame: bool,
 }
 
@@ -18,6 +24,9 @@ impl Default for Column {
             range: Rangef::new(4.0, f32::INFINITY),
             id: None,
             resizable: true,
+            reorderable: false,
+            sortable: false,
+            editable: false,
             auto_size_this_frame: false,
         }
     }
@@ -60,6 +69,35 @@ impl Column {
         self
     }
 
+    /// Can the user drag the header of this column to move it to a new position?
+    ///
+    /// The new column order is stored in [`crate::TableState::col_order`].
+    #[inline]
+    pub fn reorderable(mut self, reorderable: bool) -> Self {
+        self.reorderable = reorderable;
+        self
+    }
+
+    /// Can the user click the header of this column to sort by it?
+    ///
+    /// The sort is stored in [`crate::TableState::sort`],
+    /// and you are told about changes with [`crate::TableDelegate::on_sort_changed`].
+    #[inline]
+    pub fn sortable(mut self, sortable: bool) -> Self {
+        self.sortable = sortable;
+        self
+    }
+
+    /// Can the user edit the cells of this column?
+    ///
+    /// Editing starts with a double-click, with F2, or by typing into the focused cell.
+    /// See [`crate::TableDelegate::cell_editor_ui`] and [`crate::TableDelegate::commit_edit`].
+    #[inline]
+    pub fn editable(mut self, editable: bool) -> Self {
+        self.editable = editable;
+        self
+    }
+
     /// If set, we should accurately measure the size of this column this frame
     /// so that we can correctly auto-size it.
     ///
@@ -156,6 +194,65 @@ impl Column {
     }
 }
 
+/// The order in which to show the columns, as indices into `columns`.
+///
+/// `saved_order` is a list of column ids, as stored in [`crate::TableState::col_order`].
+/// Columns missing from it are put at the end.
+pub(crate) fn column_order(columns: &[Column], saved_order: &[Id]) -> Vec<usize> {
+    if saved_order.is_empty() {
+        return (0..columns.len()).collect();
+    }
+
+    let ids: Vec<Id> = columns
+        .iter()
+        .enumerate()
+        .map(|(col_nr, column)| column.id_for(col_nr))
+        .collect();
+
+    let mut order: Vec<usize> = saved_order
+        .iter()
+        .filter_map(|id| ids.iter().position(|col_id| col_id == id))
+        .collect();
+    let mut seen = HashSet::new();
+    order.retain(|&col_nr| seen.insert(col_nr));
+
+    for col_nr in 0..columns.len() {
+        if !order.contains(&col_nr) {
+            order.push(col_nr);
+        }
+    }
+
+    order
+}
+
+/// Move column `col_nr` so it ends up just before the column `before`,
+/// or last if `before` is `None`.
+pub(crate) fn move_column(order: &mut Vec<usize>, col_nr: usize, before: Option<usize>) {
+    if before == Some(col_nr) {
+        return;
+    }
+    order.retain(|&c| c != col_nr);
+    let index = before
+        .and_then(|before| order.iter().position(|&c| c == before))
+        .unwrap_or(order.len());
+    order.insert(index, col_nr);
+}
+
+/// Are all the members of each group next to each other in the display order?
+///
+/// `display_order` is column indices, and `groups` are ranges of column indices.
+pub(crate) fn groups_are_contiguous(display_order: &[usize], groups: &[Range<usize>]) -> bool {
+    groups.iter().all(|group| {
+        let positions: Vec<usize> = display_order
+            .iter()
+            .enumerate()
+            .filter(|(_, col_nr)| group.contains(col_nr))
+            .map(|(pos, _)| pos)
+            .collect();
+        positions.windows(2).all(|w| w[0] + 1 == w[1])
+    })
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -211,4 +308,51 @@ mod tests {
             "They should saturate the first column, then spread equally"
         );
     }
+
+    #[test]
+    fn test_column_order() {
+        let columns = [
+            Column::default().id(Id::new("a")),
+            Column::default().id(Id::new("b")),
+            Column::default().id(Id::new("c")),
+        ];
+
+        assert_eq!(column_order(&columns, &[]), [0, 1, 2]);
+        assert_eq!(
+            column_order(&columns, &[Id::new("c"), Id::new("a")]),
+            [2, 0, 1],
+            "Unknown columns go last"
+        );
+        assert_eq!(
+            column_order(&columns, &[Id::new("x"), Id::new("b")]),
+            [1, 0, 2],
+            "Removed columns are ignored"
+        );
+        assert_eq!(
+            column_order(&columns, &[Id::new("a"), Id::new("c"), Id::new("a")]),
+            [0, 2, 1],
+            "Duplicates are ignored"
+        );
+    }
+
+    #[test]
+    fn test_move_column() {
+        let mut order = vec![0, 1, 2, 3];
+        move_column(&mut order, 0, Some(3));
+        assert_eq!(order, [1, 2, 0, 3]);
+        move_column(&mut order, 3, Some(1));
+        assert_eq!(order, [3, 1, 2, 0]);
+        move_column(&mut order, 3, None);
+        assert_eq!(order, [1, 2, 0, 3]);
+        move_column(&mut order, 2, Some(2));
+        assert_eq!(order, [1, 2, 0, 3]);
+    }
+
+    #[test]
+    fn test_groups_are_contiguous() {
+        let groups = [0..1, 1..3, 3..5];
+        assert!(groups_are_contiguous(&[0, 2, 1, 4, 3], &groups));
+        assert!(groups_are_contiguous(&[3, 4, 0, 1, 2], &groups));
+        assert!(!groups_are_contiguous(&[0, 1, 3, 2, 4], &groups));
+    }
 }
@@ -0,0 +1,427 @@
+//! Writing the contents of a table as text, e.g. for copying it to the clipboard, and reading it back.
+//!
+//! See [`crate::TableDelegate::cell_text`], [`crate::TableDelegate::paste`], [`crate::Table::copy_format`]
+//! and [`crate::Table::export`].
+
+use std::io;
+
+/// How to write a table as text.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, serde::Deserialize, serde::Serialize)]
+pub enum TextFormat {
+    /// Tab-separated values, which can be pasted into spreadsheets.
+    ///
+    /// Values containing tabs, line breaks or quotes are quoted, like spreadsheets do.
+    #[default]
+    Tsv,
+
+    /// Comma-separated values, as in RFC 4180.
+    Csv,
+
+    /// A GitHub-flavored Markdown table.
+    ///
+    /// Markdown only has a single header row,
+    /// so the header cells above each column are joined, e.g. `Position / x`.
+    Markdown,
+
+    /// An HTML `<table>`.
+    Html,
+
+    /// One JSON object per line, with the values of a row as strings.
+    ///
+    /// The keys are the header cells above each column, joined like for [`Self::Markdown`].
+    /// Columns without a header get their position as key, e.g. `"2"`.
+    JsonLines,
+}
+
+/// A cell of a header row, spanning one or more columns.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct HeaderText {
+    pub text: String,
+
+    /// The number of columns this cell spans.
+    pub num_columns: usize,
+}
+
+impl HeaderText {
+    #[inline]
+    pub fn new(text: impl Into<String>, num_columns: usize) -> Self {
+        Self {
+            text: text.into(),
+            num_columns,
+        }
+    }
+}
+
+/// Writes a table as text, one row at a time.
+///
+/// Nothing is kept in memory, so this can write any number of rows.
+pub struct TableWriter<W: io::Write> {
+    writer: W,
+    format: TextFormat,
+
+    /// The keys of each column, for [`TextFormat::JsonLines`].
+    keys: Vec<String>,
+}
+
+impl<W: io::Write> TableWriter<W> {
+    /// Start a table with `num_columns` columns, by writing its header rows.
+    ///
+    /// The cells of each header row should span all the columns.
+    ///
+    /// # Errors
+    /// If writing fails.
+    pub fn new(
+        mut writer: W,
+        format: TextFormat,
+        num_columns: usize,
+        header_rows: &[Vec<HeaderText>],
+    ) -> io::Result<Self> {
+        match format {
+            TextFormat::Tsv | TextFormat::Csv => {
+                for header_row in header_rows {
+                    // The cells spanning several columns are followed by empty ones, like merged cells in a spreadsheet:
+                    let cells = header_row.iter().flat_map(|cell| {
+                        std::iter::once(cell.text.as_str())
+                            .chain(std::iter::repeat_n("", cell.num_columns.saturating_sub(1)))
+                    });
+                    write_separated_row(&mut writer, format, cells)?;
+                }
+            }
+
+            TextFormat::Markdown => {
+                write_markdown_row(&mut writer, column_titles(num_columns, header_rows))?;
+                write_markdown_row(&mut writer, std::iter::repeat_n("---", num_columns))?;
+            }
+
+            TextFormat::Html => {
+                writeln!(writer, "<table>")?;
+                if !header_rows.is_empty() {
+                    writeln!(writer, "<thead>")?;
+                    for header_row in header_rows {
+                        write!(writer, "<tr>")?;
+                        for cell in header_row {
+                            if 1 < cell.num_columns {
+                                write!(writer, "<th colspan=\"{}\">", cell.num_columns)?;
+                            } else {
+                                write!(writer, "<th>")?;
+                            }
+                            write_html_escaped(&mut writer, &cell.text)?;
+                            write!(writer, "</th>")?;
+                        }
+                        writeln!(writer, "</tr>")?;
+                    }
+                    writeln!(writer, "</thead>")?;
+                }
+                writeln!(writer, "<tbody>")?;
+            }
+
+            TextFormat::JsonLines => {}
+        }
+
+        let keys = if format == TextFormat::JsonLines {
+            column_titles(num_columns, header_rows)
+                .into_iter()
+                .enumerate()
+                .map(|(col, title)| {
+                    if title.is_empty() {
+                        col.to_string()
+                    } else {
+                        title
+                    }
+                })
+                .collect()
+        } else {
+            vec![]
+        };
+
+        Ok(Self {
+            writer,
+            format,
+            keys,
+        })
+    }
+
+    /// Write a row of the body, with one value per column.
+    ///
+    /// # Errors
+    /// If writing fails.
+    pub fn write_row<S: AsRef<str>>(
+        &mut self,
+        cells: impl IntoIterator<Item = S>,
+    ) -> io::Result<()> {
+        let writer = &mut self.writer;
+        match self.format {
+            TextFormat::Tsv | TextFormat::Csv => write_separated_row(writer, self.format, cells),
+            TextFormat::Markdown => write_markdown_row(writer, cells),
+            TextFormat::Html => {
+                write!(writer, "<tr>")?;
+                for cell in cells {
+                    write!(writer, "<td>")?;
+                    write_html_escaped(writer, cell.as_ref())?;
+                    write!(writer, "</td>")?;
+                }
+                writeln!(writer, "</tr>")
+            }
+            TextFormat::JsonLines => {
+                write!(writer, "{{")?;
+                for (col, cell) in cells.into_iter().enumerate() {
+                    if 0 < col {
+                        write!(writer, ",")?;
+                    }
+                    match self.keys.get(col) {
+                        Some(key) => write_json_string(writer, key)?,
+                        None => write_json_string(writer, &col.to_string())?,
+                    }
+                    write!(writer, ":")?;
+                    write_json_string(writer, cell.as_ref())?;
+                }
+                writeln!(writer, "}}")
+            }
+        }
+    }
+
+    /// End the table, and return the writer.
+    ///
+    /// # Errors
+    /// If writing fails.
+    pub fn finish(mut self) -> io::Result<W> {
+        if self.format == TextFormat::Html {
+            writeln!(self.writer, "</tbody>")?;
+            writeln!(self.writer, "</table>")?;
+        }
+        self.writer.flush()?;
+        Ok(self.writer)
+    }
+}
+
+/// Split tab separated (or with [`TextFormat::Csv`], comma separated) values into rows of values.
+///
+/// Quoted values may contain separators, line breaks and doubled quotes,
+/// as written by [`TableWriter`] and spreadsheets.
+/// The rows can have different numbers of values.
+pub fn parse_separated(text: &str, format: TextFormat) -> Vec<Vec<String>> {
+    let separator = if format == TextFormat::Csv { ',' } else { '\t' };
+    let mut rows = vec![];
+    let mut row = vec![];
+    let mut value = String::new();
+    let mut is_value_start = true;
+    let mut is_quoted = false;
+
+    let mut chars = text.chars().peekable();
+    while let Some(c) = chars.next() {
+        if is_quoted {
+            if c != '"' {
+                value.push(c);
+            } else if chars.next_if_eq(&'"').is_some() {
+                value.push('"');
+            } else {
+                is_quoted = false;
+            }
+        } else if c == '"' && is_value_start {
+            is_quoted = true;
+            is_value_start = false;
+        } else if c == separator {
+            row.push(std::mem::take(&mut value));
+            is_value_start = true;
+        } else if c == '\n' || c == '\r' {
+            if c == '\r' {
+                chars.next_if_eq(&'\n');
+            }
+            row.push(std::mem::take(&mut value));
+            rows.push(std::mem::take(&mut row));
+            is_value_start = true;
+        } else {
+            value.push(c);
+            is_value_start = false;
+        }
+    }
+
+    // The last line may or may not end with a line break:
+    if !is_value_start || !row.is_empty() {
+        row.push(value);
+        rows.push(row);
+    }
+    rows
+}
+
+/// The header cells above each column, joined with ` / `.
+fn column_titles(num_columns: usize, header_rows: &[Vec<HeaderText>]) -> Vec<String> {
+    let mut titles = vec![String::new(); num_columns];
+    for header_row in header_rows {
+        let mut col = 0;
+        for cell in header_row {
+            for title in titles.iter_mut().skip(col).take(cell.num_columns) {
+                if !cell.text.is_empty() {
+                    if !title.is_empty() {
+                        title.push_str(" / ");
+                    }
+                    title.push_str(&cell.text);
+                }
+            }
+            col += cell.num_columns;
+        }
+    }
+    titles
+}
+
+/// A line of tab or comma separated values.
+fn write_separated_row<S: AsRef<str>>(
+    writer: &mut impl io::Write,
+    format: TextFormat,
+    cells: impl IntoIterator<Item = S>,
+) -> io::Result<()> {
+    let separator = if format == TextFormat::Csv { ',' } else { '\t' };
+    for (i, cell) in cells.into_iter().enumerate() {
+        if 0 < i {
+            write!(writer, "{separator}")?;
+        }
+        let cell = cell.as_ref();
+        if cell.contains([separator, '"', '\n', '\r']) {
+            write!(writer, "\"{}\"", cell.replace('"', "\"\""))?;
+        } else {
+            write!(writer, "{cell}")?;
+        }
+    }
+    writeln!(writer)
+}
+
+fn write_markdown_row<S: AsRef<str>>(
+    writer: &mut impl io::Write,
+    cells: impl IntoIterator<Item = S>,
+) -> io::Result<()> {
+    write!(writer, "|")?;
+    for cell in cells {
+        let cell = cell
+            .as_ref()
+            .replace('|', "\\|")
+            .replace("\r\n", "<br>")
+            .replace('\n', "<br>");
+        write!(writer, " {cell} |")?;
+    }
+    writeln!(writer)
+}
+
+fn write_html_escaped(writer: &mut impl io::Write, text: &str) -> io::Result<()> {
+    for c in text.chars() {
+        match c {
+            '&' => write!(writer, "&amp;")?,
+            '<' => write!(writer, "&lt;")?,
+            '>' => write!(writer, "&gt;")?,
+            '"' => write!(writer, "&quot;")?,
+            '\n' => write!(writer, "<br>")?,
+            c => write!(writer, "{c}")?,
+        }
+    }
+    Ok(())
+}
+
+fn write_json_string(writer: &mut impl io::Write, text: &str) -> io::Result<()> {
+    write!(writer, "\"")?;
+    for c in text.chars() {
+        match c {
+            '"' => write!(writer, "\\\"")?,
+            '\\' => write!(writer, "\\\\")?,
+            '\n' => write!(writer, "\\n")?,
+            '\r' => write!(writer, "\\r")?,
+            '\t' => write!(writer, "\\t")?,
+            c if c.is_control() => write!(writer, "\\u{:04x}", u32::from(c))?,
+            c => write!(writer, "{c}")?,
+        }
+    }
+    write!(writer, "\"")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn write_table(format: TextFormat) -> String {
+        let header_rows = [
+            vec![HeaderText::new("", 1), HeaderText::new("Position", 2)],
+            vec![
+                HeaderText::new("Name", 1),
+                HeaderText::new("x", 1),
+                HeaderText::new("y", 1),
+            ],
+        ];
+        let mut writer =
+            TableWriter::new(vec![], format, 3, &header_rows).expect("Writing to a Vec");
+        writer
+            .write_row(["Origin", "0", "0"])
+            .expect("Writing to a Vec");
+        writer
+            .write_row(["\"Far\", away\t|<b>", "1e9", ""])
+            .expect("Writing to a Vec");
+        String::from_utf8(writer.finish().expect("Writing to a Vec")).expect("UTF-8")
+    }
+
+    #[test]
+    fn test_parse_separated() {
+        assert_eq!(
+            parse_separated("a\tb\r\n\tc\n", TextFormat::Tsv),
+            [vec!["a", "b"], vec!["", "c"]]
+        );
+        assert_eq!(
+            parse_separated("\"x, \"\"y\"\"\",\"line\nbreak\"\n1,2,3", TextFormat::Csv),
+            [vec!["x, \"y\"", "line\nbreak"], vec!["1", "2", "3"]]
+        );
+        assert_eq!(
+            parse_separated("no \"quotes\" here,", TextFormat::Csv),
+            [vec!["no \"quotes\" here", ""]],
+            "Quotes only count at the start of a value"
+        );
+        assert!(parse_separated("", TextFormat::Tsv).is_empty());
+
+        for format in [TextFormat::Tsv, TextFormat::Csv] {
+            let text = write_table(format);
+            assert_eq!(
+                parse_separated(&text, format)[3],
+                ["\"Far\", away\t|<b>", "1e9", ""],
+                "Reads back what we write"
+            );
+        }
+    }
+
+    #[test]
+    fn test_text_formats() {
+        assert_eq!(
+            write_table(TextFormat::Tsv),
+            "\tPosition\t\n\
+            Name\tx\ty\n\
+            Origin\t0\t0\n\
+            \"\"\"Far\"\", away\t|<b>\"\t1e9\t\n"
+        );
+        assert_eq!(
+            write_table(TextFormat::Csv),
+            ",Position,\n\
+            Name,x,y\n\
+            Origin,0,0\n\
+            \"\"\"Far\"\", away\t|<b>\",1e9,\n"
+        );
+        assert_eq!(
+            write_table(TextFormat::Markdown),
+            "| Name | Position / x | Position / y |\n\
+            | --- | --- | --- |\n\
+            | Origin | 0 | 0 |\n\
+            | \"Far\", away\t\\|<b> | 1e9 |  |\n"
+        );
+        assert_eq!(
+            write_table(TextFormat::Html),
+            "<table>\n\
+            <thead>\n\
+            <tr><th></th><th colspan=\"2\">Position</th></tr>\n\
+            <tr><th>Name</th><th>x</th><th>y</th></tr>\n\
+            </thead>\n\
+            <tbody>\n\
+            <tr><td>Origin</td><td>0</td><td>0</td></tr>\n\
+            <tr><td>&quot;Far&quot;, away\t|&lt;b&gt;</td><td>1e9</td><td></td></tr>\n\
+            </tbody>\n\
+            </table>\n"
+        );
+        assert_eq!(
+            write_table(TextFormat::JsonLines),
+            "{\"Name\":\"Origin\",\"Position / x\":\"0\",\"Position / y\":\"0\"}\n\
+            {\"Name\":\"\\\"Far\\\", away\\t|<b>\",\"Position / x\":\"1e9\",\"Position / y\":\"\"}\n"
+        );
+    }
+}
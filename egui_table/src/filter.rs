@@ -0,0 +1,237 @@
+//! Filtering rows with a row of filter widgets below the header.
+//!
+//! The table only keeps track of _what_ the user wants to see.
+//! Filtering the actual data is up to you, see [`crate::TableDelegate::on_filters_changed`].
+
+use std::collections::BTreeSet;
+
+use egui::{Id, IdMap, Ui};
+
+/// Which kind of filter widget to show for a column in the [`crate::Table::filter_row`].
+///
+/// See [`crate::TableDelegate::column_filter`].
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum ColumnFilter {
+    /// Only rows where the text of the cell contains what the user typed, ignoring case.
+    Text,
+
+    /// Only rows where the cell is a number between the minimum and maximum the user typed.
+    NumberRange,
+
+    /// Only rows where the cell is one of the values the user picked.
+    Choice(Vec<String>),
+}
+
+/// What the user has entered into the filter widget of a column.
+#[derive(Clone, Debug, PartialEq, Eq, serde::Deserialize, serde::Serialize)]
+pub enum FilterValue {
+    /// See [`ColumnFilter::Text`].
+    Text(String),
+
+    /// See [`ColumnFilter::NumberRange`].
+    ///
+    /// The bounds are kept as typed, so that half-typed numbers survive.
+    /// Empty bounds, and bounds that aren't numbers, don't filter anything.
+    NumberRange { min: String, max: String },
+
+    /// See [`ColumnFilter::Choice`].
+    Choice(BTreeSet<String>),
+}
+
+impl FilterValue {
+    /// A value for the given kind of filter, with nothing entered.
+    pub fn empty(filter: &ColumnFilter) -> Self {
+        match filter {
+            ColumnFilter::Text => Self::Text(String::new()),
+            ColumnFilter::NumberRange => Self::NumberRange {
+                min: String::new(),
+                max: String::new(),
+            },
+            ColumnFilter::Choice(_) => Self::Choice(BTreeSet::new()),
+        }
+    }
+
+    /// Has nothing been entered?
+    ///
+    /// Then this lets all rows through.
+    pub fn is_empty(&self) -> bool {
+        match self {
+            Self::Text(text) => text.is_empty(),
+            Self::NumberRange { min, max } => min.is_empty() && max.is_empty(),
+            Self::Choice(chosen) => chosen.is_empty(),
+        }
+    }
+
+    /// Does a cell with this text pass the filter?
+    pub fn matches(&self, text: &str) -> bool {
+        match self {
+            Self::Text(needle) => text.to_lowercase().contains(&needle.to_lowercase()),
+            Self::NumberRange { min, max } => {
+                let (min, max) = (parse_number(min), parse_number(max));
+                if min.is_none() && max.is_none() {
+                    return true;
+                }
+                parse_number(text).is_some_and(|value| {
+                    min.is_none_or(|min| min <= value) && max.is_none_or(|max| value <= max)
+                })
+            }
+            Self::Choice(chosen) => chosen.is_empty() || chosen.contains(text),
+        }
+    }
+}
+
+fn parse_number(text: &str) -> Option<f64> {
+    text.trim().parse().ok()
+}
+
+/// What the user has entered into the [`crate::Table::filter_row`].
+///
+/// This is stored in [`crate::TableState::filters`].
+#[derive(Clone, Debug, Default, PartialEq, Eq, serde::Deserialize, serde::Serialize)]
+#[serde(default)]
+pub struct FilterState {
+    /// Keyed by the [`crate::Column::id_for`] of the column.
+    ///
+    /// Columns where nothing has been entered are left out.
+    pub values: IdMap<FilterValue>,
+}
+
+impl FilterState {
+    /// Does this let all rows through?
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.values.is_empty()
+    }
+
+    pub fn clear(&mut self) {
+        self.values.clear();
+    }
+
+    /// The filter of the given column, if it has one.
+    pub fn get(&self, column_id: Id) -> Option<&FilterValue> {
+        self.values.get(&column_id)
+    }
+
+    /// Set the filter of the given column, or remove it if nothing has been entered.
+    pub fn set(&mut self, column_id: Id, value: FilterValue) {
+        if value.is_empty() {
+            self.values.remove(&column_id);
+        } else {
+            self.values.insert(column_id, value);
+        }
+    }
+
+    /// Does a cell with this text pass the filter of its column?
+    ///
+    /// A row should only be shown if all its cells pass.
+    pub fn matches(&self, column_id: Id, text: &str) -> bool {
+        self.get(column_id).is_none_or(|value| value.matches(text))
+    }
+}
+
+/// Show a filter widget filling the given [`Ui`], for a cell in the [`crate::Table::filter_row`].
+///
+/// Returns `true` if the user changed the value.
+///
+/// This is the default implementation of [`crate::TableDelegate::filter_ui`].
+pub fn filter_ui(ui: &mut Ui, filter: &ColumnFilter, value: &mut FilterValue) -> bool {
+    let width = ui.available_width();
+    match (filter, value) {
+        (ColumnFilter::Text, FilterValue::Text(text)) => ui
+            .add(
+                egui::TextEdit::singleline(text)
+                    .hint_text("Filter")
+                    .desired_width(width),
+            )
+            .changed(),
+
+        (ColumnFilter::NumberRange, FilterValue::NumberRange { min, max }) => {
+            let width = 0.5 * (width - ui.spacing().item_spacing.x);
+            let mut changed = false;
+            for (bound, hint) in [(min, "Min"), (max, "Max")] {
+                changed |= ui
+                    .add(
+                        egui::TextEdit::singleline(bound)
+                            .hint_text(hint)
+                            .desired_width(width),
+                    )
+                    .changed();
+            }
+            changed
+        }
+
+        (ColumnFilter::Choice(options), FilterValue::Choice(chosen)) => {
+            let selected_text = match chosen.len() {
+                0 => "All".to_owned(),
+                1 => chosen.iter().next().cloned().unwrap_or_default(),
+                num_chosen => format!("{num_chosen} chosen"),
+            };
+            let mut changed = false;
+            egui::ComboBox::from_id_salt("filter")
+                .selected_text(selected_text)
+                .width(width)
+                .close_behavior(egui::PopupCloseBehavior::CloseOnClickOutside)
+                .show_ui(ui, |ui| {
+                    for option in options {
+                        let mut is_chosen = chosen.contains(option);
+                        if ui.checkbox(&mut is_chosen, option.as_str()).changed() {
+                            if is_chosen {
+                                chosen.insert(option.clone());
+                            } else {
+                                chosen.remove(option);
+                            }
+                            changed = true;
+                        }
+                    }
+                });
+            changed
+        }
+
+        _ => false, // The value is for another kind of filter
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_filter_values() {
+        let text = FilterValue::Text("ell".to_owned());
+        assert!(text.matches("Hello"));
+        assert!(text.matches("BELL"), "Ignores case");
+        assert!(!text.matches("world"));
+
+        let range = |min: &str, max: &str| FilterValue::NumberRange {
+            min: min.to_owned(),
+            max: max.to_owned(),
+        };
+        assert!(range("10", "20").matches("10"));
+        assert!(range("10", "20").matches(" 20 "));
+        assert!(!range("10", "20").matches("21"));
+        assert!(!range("10", "").matches("text"));
+        assert!(
+            range("-", "5").matches("-3"),
+            "Half-typed bounds are ignored"
+        );
+        assert!(
+            !range("-", "").is_empty() && range("-", "").matches("text"),
+            "Half-typed bounds are kept, but let all rows through"
+        );
+
+        let choice = FilterValue::Choice(["a".to_owned(), "b".to_owned()].into());
+        assert!(choice.matches("a"));
+        assert!(!choice.matches("c"));
+        assert!(FilterValue::empty(&ColumnFilter::Choice(vec![])).matches("c"));
+
+        let mut filters = FilterState::default();
+        let column_id = Id::new("column");
+        filters.set(column_id, text);
+        assert!(filters.matches(column_id, "yellow"));
+        assert!(!filters.matches(column_id, "blue"));
+        assert!(filters.matches(Id::new("other"), "blue"));
+
+        filters.set(column_id, FilterValue::empty(&ColumnFilter::Text));
+        assert!(filters.is_empty(), "Empty filters are removed");
+    }
+}
@@ -3,12 +3,27 @@
 //! See [`Table`].
 
 pub mod columns;
+pub mod export;
+pub mod filter;
+mod navigation;
+mod row_heights;
+mod row_mapping;
+pub mod selection;
+pub mod sort;
 mod split_scroll;
 mod table;
+pub mod tree;
 
 pub use columns::Column;
+pub use export::TextFormat;
+pub use filter::{ColumnFilter, FilterState, FilterValue};
+pub use row_heights::RowHeights;
+pub use row_mapping::RowMapping;
+pub use selection::{CellCoord, CellRange, CellSelection, RowSet, Selection, SelectionMode};
+pub use sort::{SortDirection, SortKey, SortState};
 pub use split_scroll::{SplitScroll, SplitScrollDelegate};
 pub use table::{
-    AutoSizeMode, CellInfo, HeaderCellInfo, HeaderRow, PrefetchInfo, Table, TableDelegate,
-    TableState,
+    AutoSizeMode, CellInfo, FooterCellInfo, FooterRow, HeaderCellInfo, HeaderRow, PrefetchInfo,
+    Table, TableDelegate, TableResponse, TableState,
 };
+pub use tree::TreeNode;
@@ -0,0 +1,93 @@
+//! Moving the focused cell with the keyboard.
+
+use egui::{Key, Modifiers};
+
+use crate::CellCoord;
+
+/// The keys used to move the focused cell.
+pub(crate) const NAVIGATION_KEYS: [Key; 8] = [
+    Key::ArrowUp,
+    Key::ArrowDown,
+    Key::ArrowLeft,
+    Key::ArrowRight,
+    Key::PageUp,
+    Key::PageDown,
+    Key::Home,
+    Key::End,
+];
+
+/// Where should the focus move to when the user presses the given key?
+///
+/// `page_size` is the number of rows that Page Up and Page Down moves.
+///
+/// Returns `None` if the key is not a navigation key,
+/// or if the table is empty.
+pub(crate) fn move_focus(
+    focus: CellCoord,
+    key: Key,
+    modifiers: Modifiers,
+    num_rows: u64,
+    num_columns: usize,
+    page_size: u64,
+) -> Option<CellCoord> {
+    if num_rows == 0 || num_columns == 0 {
+        return None;
+    }
+
+    let last_row = num_rows - 1;
+    let last_col = num_columns - 1;
+    let CellCoord { row_nr, col_pos } = focus;
+
+    let (row_nr, col_pos) = match key {
+        Key::ArrowUp => (row_nr.saturating_sub(1), col_pos),
+        Key::ArrowDown => (row_nr + 1, col_pos),
+        Key::ArrowLeft => (row_nr, col_pos.saturating_sub(1)),
+        Key::ArrowRight => (row_nr, col_pos + 1),
+        Key::PageUp => (row_nr.saturating_sub(page_size), col_pos),
+        Key::PageDown => (row_nr.saturating_add(page_size), col_pos),
+        Key::Home if modifiers.command => (0, 0),
+        Key::End if modifiers.command => (last_row, last_col),
+        Key::Home => (row_nr, 0),
+        Key::End => (row_nr, last_col),
+        _ => return None,
+    };
+
+    Some(CellCoord {
+        row_nr: row_nr.min(last_row),
+        col_pos: col_pos.min(last_col),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn cell(row_nr: u64, col_pos: usize) -> CellCoord {
+        CellCoord { row_nr, col_pos }
+    }
+
+    #[test]
+    fn test_move_focus() {
+        let none = Modifiers::NONE;
+        let command = Modifiers::COMMAND;
+        let mv = |focus, key, modifiers| move_focus(focus, key, modifiers, 100, 5, 10);
+
+        assert_eq!(mv(cell(0, 0), Key::ArrowUp, none), Some(cell(0, 0)));
+        assert_eq!(mv(cell(0, 0), Key::ArrowDown, none), Some(cell(1, 0)));
+        assert_eq!(mv(cell(3, 4), Key::ArrowRight, none), Some(cell(3, 4)));
+        assert_eq!(mv(cell(3, 4), Key::ArrowLeft, none), Some(cell(3, 3)));
+        assert_eq!(mv(cell(95, 2), Key::PageDown, none), Some(cell(99, 2)));
+        assert_eq!(mv(cell(15, 2), Key::PageUp, none), Some(cell(5, 2)));
+        assert_eq!(mv(cell(15, 2), Key::Home, none), Some(cell(15, 0)));
+        assert_eq!(mv(cell(15, 2), Key::End, none), Some(cell(15, 4)));
+        assert_eq!(mv(cell(15, 2), Key::Home, command), Some(cell(0, 0)));
+        assert_eq!(mv(cell(15, 2), Key::End, command), Some(cell(99, 4)));
+        assert_eq!(mv(cell(15, 2), Key::A, none), None);
+
+        assert_eq!(
+            move_focus(cell(0, 0), Key::ArrowDown, none, 0, 5, 10),
+            None,
+            "Empty table"
+        );
+    }
+}
@@ -0,0 +1,225 @@
+//! Fast lookup of row offsets when only a few rows have a height of their own.
+
+use std::collections::{BTreeMap, HashMap};
+
+/// The height of each row of a table, where most rows have the same default height.
+///
+/// Answers [`Self::top_offset`] and its inverse [`Self::row_at_offset`] in `O(log n)`,
+/// and [`Self::set_height`] is `O(log n)` too.
+/// Memory use only depends on how many rows have a height of their own,
+/// so this works for any number of rows.
+///
+/// Use this to implement [`crate::TableDelegate::row_top_offset_f64`]
+/// when your rows have different heights.
+/// [`crate::Table::auto_row_heights`] uses it for the measured heights.
+#[derive(Clone, Debug, Default)]
+pub struct RowHeights {
+    default_height: f32,
+
+    /// The rows that don't have the default height.
+    heights: BTreeMap<u64, f32>,
+
+    /// A Fenwick tree of how much each row differs from the default height.
+    ///
+    /// The node at index `i` (starting at 1) is the sum of rows `i - (i & -i)` up to (but not including) `i`.
+    /// Nodes that were never changed are left out.
+    tree: HashMap<u64, f64>,
+}
+
+impl RowHeights {
+    /// All rows are `default_height` high.
+    pub fn new(default_height: f32) -> Self {
+        Self {
+            default_height,
+            heights: Default::default(),
+            tree: Default::default(),
+        }
+    }
+
+    /// The height of rows that haven't been given a height of their own.
+    #[inline]
+    pub fn default_height(&self) -> f32 {
+        self.default_height
+    }
+
+    /// Change the height of the rows that haven't been given a height of their own.
+    ///
+    /// This is `O(k log n)`, where `k` is the number of rows with a height of their own.
+    pub fn set_default_height(&mut self, default_height: f32) {
+        if self.default_height != default_height {
+            let heights = std::mem::take(&mut self.heights);
+            self.tree.clear();
+            self.default_height = default_height;
+            for (row_nr, height) in heights {
+                self.set_height(row_nr, height);
+            }
+        }
+    }
+
+    /// The height of the given row.
+    pub fn height(&self, row_nr: u64) -> f32 {
+        self.heights
+            .get(&row_nr)
+            .copied()
+            .unwrap_or(self.default_height)
+    }
+
+    /// Give a row a height of its own.
+    pub fn set_height(&mut self, row_nr: u64, height: f32) {
+        let old_height = self.height(row_nr);
+        if height == self.default_height {
+            self.heights.remove(&row_nr);
+        } else {
+            self.heights.insert(row_nr, height);
+        }
+        self.add(row_nr, f64::from(height) - f64::from(old_height));
+    }
+
+    /// Go back to the default height for the given row.
+    pub fn reset_height(&mut self, row_nr: u64) {
+        self.set_height(row_nr, self.default_height);
+    }
+
+    /// Go back to the default height for all rows.
+    pub fn clear(&mut self) {
+        self.heights.clear();
+        self.tree.clear();
+    }
+
+    /// The rows with a height of their own, and their heights.
+    pub fn iter(&self) -> impl Iterator<Item = (u64, f32)> + '_ {
+        self.heights
+            .iter()
+            .map(|(&row_nr, &height)| (row_nr, height))
+    }
+
+    /// Do all rows have the default height?
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.heights.is_empty()
+    }
+
+    /// The sum of the heights of all rows before the given one.
+    pub fn top_offset(&self, row_nr: u64) -> f64 {
+        let mut offset = row_nr as f64 * f64::from(self.default_height);
+        let mut index = row_nr;
+        while 0 < index {
+            offset += self.node(index);
+            index &= index - 1; // Remove the lowest bit
+        }
+        offset
+    }
+
+    /// Which row contains the given offset from the top of the first row?
+    ///
+    /// This is the inverse of [`Self::top_offset`].
+    /// The rows are assumed to go on forever, so clamp the result to the number of rows you have.
+    pub fn row_at_offset(&self, offset: f64) -> u64 {
+        let default_height = f64::from(self.default_height);
+        let mut remaining = offset;
+        let mut row_nr = 0_u64;
+
+        // Walk down the Fenwick tree, skipping over every node that ends above the offset:
+        for bit in (0..u64::BITS).rev() {
+            let num_rows = 1_u64 << bit;
+            let Some(index) = row_nr.checked_add(num_rows) else {
+                continue;
+            };
+            let height = num_rows as f64 * default_height + self.node(index);
+            if height <= remaining {
+                row_nr = index;
+                remaining -= height;
+            }
+        }
+        row_nr
+    }
+
+    fn node(&self, index: u64) -> f64 {
+        self.tree.get(&index).copied().unwrap_or_default()
+    }
+
+    /// Add `delta` to the height of the given row, in the Fenwick tree.
+    fn add(&mut self, row_nr: u64, delta: f64) {
+        if delta == 0.0 {
+            return;
+        }
+        let Some(mut index) = row_nr.checked_add(1) else {
+            return; // Nobody has that many rows
+        };
+        loop {
+            *self.tree.entry(index).or_default() += delta;
+            let lowest_bit = index & index.wrapping_neg();
+            match index.checked_add(lowest_bit) {
+                Some(next) => index = next,
+                None => break,
+            }
+        }
+    }
+}
+
+/// For when we know the value is small enough to fit, e.g. the difference between two row offsets.
+#[expect(clippy::cast_possible_truncation)]
+pub(crate) fn to_f32(value: f64) -> f32 {
+    value as f32
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_row_heights() {
+        let mut heights = RowHeights::new(20.0);
+        assert_eq!(heights.top_offset(5), 100.0);
+        assert_eq!(heights.row_at_offset(99.0), 4);
+        assert_eq!(heights.row_at_offset(100.0), 5);
+
+        heights.set_height(2, 50.0);
+        heights.set_height(7, 0.0);
+        heights.set_height(100_000, 120.0);
+        let naive_top_offset = |row_nr: u64| -> f64 {
+            (0..row_nr.min(10))
+                .map(|row| f64::from(heights.height(row)))
+                .sum()
+        };
+        for row_nr in 0..10 {
+            assert_eq!(heights.top_offset(row_nr), naive_top_offset(row_nr));
+        }
+        assert_eq!(heights.row_at_offset(0.0), 0);
+        assert_eq!(heights.row_at_offset(40.0), 2);
+        assert_eq!(heights.row_at_offset(89.0), 2);
+        assert_eq!(heights.row_at_offset(90.0), 3);
+        assert_eq!(heights.row_at_offset(170.0), 8, "Skips the empty row 7");
+        assert_eq!(
+            heights.top_offset(100_001),
+            100_001.0 * 20.0 + 30.0 - 20.0 + 100.0
+        );
+        assert_eq!(heights.row_at_offset(2_000_129.0), 100_000);
+
+        // Far beyond what `f32` can tell apart:
+        let billions = 5_000_000_000;
+        heights.set_height(billions, 20.5);
+        assert_eq!(
+            heights.top_offset(billions + 1) - heights.top_offset(billions),
+            20.5
+        );
+        assert_eq!(
+            heights.row_at_offset(heights.top_offset(billions) + 20.0),
+            billions
+        );
+        heights.reset_height(billions);
+
+        heights.reset_height(2);
+        assert_eq!(heights.top_offset(5), 100.0);
+
+        heights.set_default_height(10.0);
+        assert_eq!(heights.height(2), 10.0);
+        assert_eq!(heights.height(7), 0.0);
+        assert_eq!(heights.top_offset(10), 90.0);
+        assert_eq!(heights.row_at_offset(70.0), 8);
+
+        heights.clear();
+        assert!(heights.is_empty());
+        assert_eq!(heights.top_offset(10), 100.0);
+    }
+}
@@ -0,0 +1,135 @@
+//! Mapping the rows shown in a table to the rows of the data source.
+
+use std::{cmp::Ordering, ops::Range};
+
+/// Which row of your data source is shown at each visual row of a [`crate::Table`].
+///
+/// Use this when showing a sorted and/or filtered view of your data,
+/// so that you don't need to translate row numbers in every [`crate::TableDelegate`] callback.
+/// See [`crate::Table::row_mapping`] and [`crate::CellInfo::source_row`].
+///
+/// This is a permutation of (or a subset of) the source rows, stored as one `u64` per visual row.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct RowMapping {
+    /// The source row of each visual row.
+    source_rows: Vec<u64>,
+}
+
+impl RowMapping {
+    /// The source row of each visual row.
+    #[inline]
+    pub fn new(source_rows: Vec<u64>) -> Self {
+        Self { source_rows }
+    }
+
+    /// Show all `num_source_rows` rows, in their original order.
+    pub fn identity(num_source_rows: u64) -> Self {
+        Self::new((0..num_source_rows).collect())
+    }
+
+    /// Show all `num_source_rows` rows, sorted with the given comparison of source rows.
+    ///
+    /// The sort is stable, so equal rows keep their original order.
+    pub fn sorted_by(num_source_rows: u64, compare: impl FnMut(&u64, &u64) -> Ordering) -> Self {
+        let mut mapping = Self::identity(num_source_rows);
+        mapping.sort_by(compare);
+        mapping
+    }
+
+    /// Only show the source rows for which `keep` returns `true`, in their original order.
+    pub fn filtered(num_source_rows: u64, mut keep: impl FnMut(u64) -> bool) -> Self {
+        Self::new((0..num_source_rows).filter(|&row| keep(row)).collect())
+    }
+
+    /// Reorder the visual rows with the given comparison of source rows.
+    ///
+    /// The sort is stable, so equal rows keep their current order.
+    pub fn sort_by(&mut self, compare: impl FnMut(&u64, &u64) -> Ordering) {
+        self.source_rows.sort_by(compare);
+    }
+
+    /// Only keep the visual rows whose source row `keep` returns `true` for.
+    pub fn retain(&mut self, mut keep: impl FnMut(u64) -> bool) {
+        self.source_rows.retain(|&row| keep(row));
+    }
+
+    /// Reverse the order of the visual rows.
+    pub fn reverse(&mut self) {
+        self.source_rows.reverse();
+    }
+
+    /// Number of visual rows.
+    #[inline]
+    pub fn len(&self) -> u64 {
+        self.source_rows.len() as u64
+    }
+
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.source_rows.is_empty()
+    }
+
+    /// The source row shown at the given visual row, if any.
+    #[inline]
+    pub fn source_row(&self, row_nr: u64) -> Option<u64> {
+        let index = usize::try_from(row_nr).ok()?;
+        self.source_rows.get(index).copied()
+    }
+
+    /// The source rows shown at the given range of visual rows,
+    /// e.g. [`crate::PrefetchInfo::visible_rows`].
+    pub fn source_rows(&self, rows: Range<u64>) -> &[u64] {
+        let len = self.source_rows.len();
+        let to_index = |row_nr: u64| usize::try_from(row_nr).unwrap_or(len).min(len);
+        let start = to_index(rows.start);
+        let end = to_index(rows.end).max(start);
+        &self.source_rows[start..end]
+    }
+
+    /// The visual row where the given source row is shown, if it is shown at all.
+    ///
+    /// This is a linear search, so avoid calling it for every row.
+    pub fn visual_row(&self, source_row: u64) -> Option<u64> {
+        self.source_rows
+            .iter()
+            .position(|&row| row == source_row)
+            .map(|index| index as u64)
+    }
+
+    /// The source row of each visual row.
+    #[inline]
+    pub fn as_slice(&self) -> &[u64] {
+        &self.source_rows
+    }
+}
+
+impl From<Vec<u64>> for RowMapping {
+    #[inline]
+    fn from(source_rows: Vec<u64>) -> Self {
+        Self::new(source_rows)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_row_mapping() {
+        let mut mapping = RowMapping::sorted_by(6, |a, b| (a % 3).cmp(&(b % 3)));
+        assert_eq!(mapping.as_slice(), [0, 3, 1, 4, 2, 5], "Stable sort");
+        assert_eq!(mapping.source_row(2), Some(1));
+        assert_eq!(mapping.source_row(6), None);
+        assert_eq!(mapping.visual_row(4), Some(3));
+        assert_eq!(mapping.source_rows(4..100), [2, 5]);
+        assert_eq!(mapping.source_rows(100..200), [] as [u64; 0]);
+
+        mapping.retain(|row| row % 2 == 0);
+        assert_eq!(mapping.as_slice(), [0, 4, 2]);
+        assert_eq!(mapping.visual_row(3), None);
+
+        let mapping = RowMapping::filtered(10, |row| 7 <= row);
+        assert_eq!(mapping.as_slice(), [7, 8, 9]);
+        assert_eq!(mapping.len(), 3);
+    }
+}
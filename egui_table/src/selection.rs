@@ -0,0 +1,453 @@
+//! Row and cell selection.
+
+use std::{
+    collections::BTreeMap,
+    ops::{Range, RangeInclusive},
+};
+
+/// How the user can select rows in a [`crate::Table`].
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, serde::Deserialize, serde::Serialize)]
+pub enum SelectionMode {
+    /// Rows can't be selected.
+    #[default]
+    None,
+
+    /// At most one row can be selected at a time.
+    Single,
+
+    /// Click to select one row, Ctrl/Cmd-click to toggle individual rows.
+    Multi,
+
+    /// Like [`Self::Multi`], but Shift-click also selects the range of rows
+    /// between the last clicked row and the clicked row.
+    Range,
+
+    /// Spreadsheet-style selection of a rectangle of cells.
+    ///
+    /// Click a cell and drag to select a rectangle.
+    /// Shift-click to extend the selection to the clicked cell.
+    Cells,
+}
+
+/// The coordinate of a cell in the body of a [`crate::Table`].
+///
+/// Note that this uses the display position of the column, which is different from
+/// the column index if the user has reordered the columns.
+/// See [`crate::CellInfo::col_pos`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, serde::Deserialize, serde::Serialize)]
+pub struct CellCoord {
+    pub row_nr: u64,
+
+    /// Display position of the column.
+    pub col_pos: usize,
+}
+
+/// A rectangular range of cells.
+#[derive(Clone, Debug, PartialEq, Eq, serde::Deserialize, serde::Serialize)]
+pub struct CellRange {
+    pub rows: RangeInclusive<u64>,
+
+    /// Display positions of the columns.
+    ///
+    /// Use [`crate::TableState::column_at`] to get the column indices.
+    pub columns: RangeInclusive<usize>,
+}
+
+impl CellRange {
+    #[inline]
+    pub fn contains(&self, cell: CellCoord) -> bool {
+        self.rows.contains(&cell.row_nr) && self.columns.contains(&cell.col_pos)
+    }
+}
+
+/// A rectangle of selected cells, spanned by two corner cells.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, serde::Deserialize, serde::Serialize)]
+pub struct CellSelection {
+    /// Where the selection started.
+    pub anchor: CellCoord,
+
+    /// The opposite corner to the anchor, e.g. where the pointer was dragged to.
+    pub focus: CellCoord,
+}
+
+impl CellSelection {
+    /// The selected rectangle.
+    pub fn range(&self) -> CellRange {
+        let Self { anchor, focus } = self;
+        CellRange {
+            rows: anchor.row_nr.min(focus.row_nr)..=anchor.row_nr.max(focus.row_nr),
+            columns: anchor.col_pos.min(focus.col_pos)..=anchor.col_pos.max(focus.col_pos),
+        }
+    }
+}
+
+/// A set of row numbers, stored as non-overlapping ranges.
+///
+/// This makes it cheap to select millions of rows at once.
+#[derive(Clone, Debug, Default, PartialEq, Eq, serde::Deserialize, serde::Serialize)]
+pub struct RowSet {
+    /// Maps the start of each range to its (exclusive) end.
+    ///
+    /// The ranges are non-empty, non-overlapping, and never adjacent.
+    ranges: BTreeMap<u64, u64>,
+}
+
+impl RowSet {
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.ranges.is_empty()
+    }
+
+    /// Total number of rows in the set.
+    pub fn len(&self) -> u64 {
+        self.ranges.iter().map(|(start, end)| end - start).sum()
+    }
+
+    pub fn contains(&self, row_nr: u64) -> bool {
+        self.ranges
+            .range(..=row_nr)
+            .next_back()
+            .is_some_and(|(_, &end)| row_nr < end)
+    }
+
+    pub fn clear(&mut self) {
+        self.ranges.clear();
+    }
+
+    pub fn insert(&mut self, row_nr: u64) {
+        self.insert_range(row_nr..row_nr + 1);
+    }
+
+    pub fn remove(&mut self, row_nr: u64) {
+        self.remove_range(row_nr..row_nr + 1);
+    }
+
+    pub fn insert_range(&mut self, range: Range<u64>) {
+        if range.is_empty() {
+            return;
+        }
+        let mut start = range.start;
+        let mut end = range.end;
+
+        // Merge with a range that starts before us and touches us:
+        if let Some((&prev_start, &prev_end)) = self.ranges.range(..=start).next_back()
+            && start <= prev_end
+        {
+            start = prev_start;
+            end = end.max(prev_end);
+        }
+
+        // Merge with all ranges that start within (or right after) us:
+        let overlapping: Vec<u64> = self.ranges.range(start..=end).map(|(&s, _)| s).collect();
+        for s in overlapping {
+            if let Some(e) = self.ranges.remove(&s) {
+                end = end.max(e);
+            }
+        }
+
+        self.ranges.insert(start, end);
+    }
+
+    pub fn remove_range(&mut self, range: Range<u64>) {
+        if range.is_empty() {
+            return;
+        }
+
+        // Split a range that starts before us:
+        if let Some((&prev_start, &prev_end)) = self.ranges.range(..range.start).next_back()
+            && range.start < prev_end
+        {
+            self.ranges.insert(prev_start, range.start);
+            if range.end < prev_end {
+                self.ranges.insert(range.end, prev_end);
+                return;
+            }
+        }
+
+        // Remove or trim all ranges that start within us:
+        let overlapping: Vec<(u64, u64)> = self
+            .ranges
+            .range(range.clone())
+            .map(|(&s, &e)| (s, e))
+            .collect();
+        for (s, e) in overlapping {
+            self.ranges.remove(&s);
+            if range.end < e {
+                self.ranges.insert(range.end, e);
+            }
+        }
+    }
+
+    /// Iterate over the non-overlapping ranges, in order.
+    pub fn ranges(&self) -> impl Iterator<Item = Range<u64>> + '_ {
+        self.ranges.iter().map(|(&start, &end)| start..end)
+    }
+
+    /// Iterate over all rows in the set, in order.
+    pub fn iter(&self) -> impl Iterator<Item = u64> + '_ {
+        self.ranges().flatten()
+    }
+}
+
+/// The selected rows or cells of a [`crate::Table`].
+///
+/// This is stored in [`crate::TableState`].
+#[derive(Clone, Debug, Default, PartialEq, Eq, serde::Deserialize, serde::Serialize)]
+#[serde(default)]
+pub struct Selection {
+    /// The selected rows.
+    pub rows: RowSet,
+
+    /// The row that was last clicked without Shift held.
+    ///
+    /// Shift-clicking selects from here to the clicked row.
+    pub anchor: Option<u64>,
+
+    /// The selected rectangle of cells, used by [`SelectionMode::Cells`].
+    pub cells: Option<CellSelection>,
+}
+
+impl Selection {
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.rows.is_empty() && self.cells.is_none()
+    }
+
+    #[inline]
+    pub fn is_row_selected(&self, row_nr: u64) -> bool {
+        self.rows.contains(row_nr)
+    }
+
+    #[inline]
+    pub fn is_cell_selected(&self, cell: CellCoord) -> bool {
+        self.cells.is_some_and(|cells| cells.range().contains(cell))
+    }
+
+    pub fn clear(&mut self) {
+        self.rows.clear();
+        self.anchor = None;
+        self.cells = None;
+    }
+
+    /// Select only this one row.
+    pub fn select_row(&mut self, row_nr: u64) {
+        self.rows.clear();
+        self.rows.insert(row_nr);
+        self.anchor = Some(row_nr);
+    }
+
+    /// Update the selection as a response to the user pressing a cell.
+    ///
+    /// Holding Shift extends the current selection to the cell.
+    pub fn on_cell_press(&mut self, cell: CellCoord, modifiers: egui::Modifiers) {
+        self.rows.clear();
+        self.anchor = None;
+        match &mut self.cells {
+            Some(cells) if modifiers.shift => {
+                cells.focus = cell;
+            }
+            _ => {
+                self.cells = Some(CellSelection {
+                    anchor: cell,
+                    focus: cell,
+                });
+            }
+        }
+    }
+
+    /// Update the selection as a response to the user moving the focused cell with the keyboard.
+    ///
+    /// The selection follows the focus, and holding Shift extends the selection.
+    pub fn on_focus_move(
+        &mut self,
+        mode: SelectionMode,
+        cell: CellCoord,
+        modifiers: egui::Modifiers,
+    ) {
+        match mode {
+            SelectionMode::None => {}
+
+            SelectionMode::Single | SelectionMode::Multi => {
+                self.select_row(cell.row_nr);
+            }
+
+            SelectionMode::Range => {
+                if modifiers.shift
+                    && let Some(anchor) = self.anchor
+                {
+                    self.rows.clear();
+                    self.rows
+                        .insert_range(anchor.min(cell.row_nr)..anchor.max(cell.row_nr) + 1);
+                } else {
+                    self.select_row(cell.row_nr);
+                }
+            }
+
+            SelectionMode::Cells => {
+                self.on_cell_press(cell, modifiers);
+            }
+        }
+    }
+
+    /// Update the selection as a response to the user clicking a row.
+    ///
+    /// `command` is Ctrl (or Cmd on Mac), and `shift` is Shift.
+    pub fn on_click(&mut self, mode: SelectionMode, row_nr: u64, modifiers: egui::Modifiers) {
+        match mode {
+            SelectionMode::None | SelectionMode::Cells => {}
+
+            SelectionMode::Single => {
+                if modifiers.command && self.is_row_selected(row_nr) {
+                    self.clear();
+                } else {
+                    self.select_row(row_nr);
+                }
+            }
+
+            SelectionMode::Multi | SelectionMode::Range => {
+                if mode == SelectionMode::Range
+                    && modifiers.shift
+                    && let Some(anchor) = self.anchor
+                {
+                    if !modifiers.command {
+                        self.rows.clear();
+                    }
+                    let range = anchor.min(row_nr)..anchor.max(row_nr) + 1;
+                    self.rows.insert_range(range);
+                    // Keep the anchor, so that we can shift-click again to change the range.
+                } else if modifiers.command {
+                    if self.is_row_selected(row_nr) {
+                        self.rows.remove(row_nr);
+                    } else {
+                        self.rows.insert(row_nr);
+                    }
+                    self.anchor = Some(row_nr);
+                } else {
+                    self.select_row(row_nr);
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use egui::Modifiers;
+
+    use super::*;
+
+    fn ranges(set: &RowSet) -> Vec<(u64, u64)> {
+        set.ranges().map(|r| (r.start, r.end)).collect()
+    }
+
+    #[test]
+    fn test_row_set_insert() {
+        let mut set = RowSet::default();
+        set.insert_range(10..20);
+        set.insert_range(30..40);
+        assert_eq!(ranges(&set), [(10, 20), (30, 40)]);
+
+        set.insert(20);
+        assert_eq!(
+            ranges(&set),
+            [(10, 21), (30, 40)],
+            "Adjacent ranges should merge"
+        );
+
+        set.insert_range(15..35);
+        assert_eq!(ranges(&set), [(10, 40)]);
+
+        set.insert_range(0..1_000_000_000);
+        assert_eq!(ranges(&set), [(0, 1_000_000_000)]);
+        assert_eq!(set.len(), 1_000_000_000);
+    }
+
+    #[test]
+    fn test_row_set_remove() {
+        let mut set = RowSet::default();
+        set.insert_range(10..20);
+        set.insert_range(30..40);
+
+        set.remove(15);
+        assert_eq!(ranges(&set), [(10, 15), (16, 20), (30, 40)]);
+        assert!(!set.contains(15));
+        assert!(set.contains(16));
+
+        set.remove_range(18..35);
+        assert_eq!(ranges(&set), [(10, 15), (16, 18), (35, 40)]);
+
+        set.remove_range(0..100);
+        assert!(set.is_empty());
+    }
+
+    #[test]
+    fn test_selection_clicks() {
+        let plain = Modifiers::NONE;
+        let command = Modifiers::COMMAND;
+        let shift = Modifiers::SHIFT;
+
+        let mut selection = Selection::default();
+        selection.on_click(SelectionMode::Range, 5, plain);
+        selection.on_click(SelectionMode::Range, 8, shift);
+        assert_eq!(ranges(&selection.rows), [(5, 9)]);
+
+        selection.on_click(SelectionMode::Range, 2, shift);
+        assert_eq!(
+            ranges(&selection.rows),
+            [(2, 6)],
+            "Shift-click keeps the anchor"
+        );
+
+        selection.on_click(SelectionMode::Range, 10, command);
+        assert_eq!(ranges(&selection.rows), [(2, 6), (10, 11)]);
+
+        selection.on_click(SelectionMode::Range, 3, command);
+        assert_eq!(ranges(&selection.rows), [(2, 3), (4, 6), (10, 11)]);
+
+        selection.on_click(SelectionMode::Single, 7, command);
+        assert_eq!(ranges(&selection.rows), [(7, 8)]);
+
+        selection.on_click(SelectionMode::Single, 7, command);
+        assert!(selection.is_empty());
+    }
+
+    #[test]
+    fn test_cell_selection() {
+        let cell = |row_nr, col_pos| CellCoord { row_nr, col_pos };
+
+        let mut selection = Selection::default();
+        selection.on_cell_press(cell(10, 4), Modifiers::NONE);
+        selection.on_cell_press(cell(2, 7), Modifiers::SHIFT);
+
+        let range = selection.cells.map(|cells| cells.range());
+        assert_eq!(
+            range,
+            Some(CellRange {
+                rows: 2..=10,
+                columns: 4..=7,
+            })
+        );
+        assert!(selection.is_cell_selected(cell(5, 5)));
+        assert!(!selection.is_cell_selected(cell(5, 8)));
+
+        selection.on_cell_press(cell(1, 1), Modifiers::NONE);
+        assert!(!selection.is_cell_selected(cell(5, 5)));
+    }
+
+    #[test]
+    fn test_focus_move() {
+        let cell = |row_nr| CellCoord { row_nr, col_pos: 0 };
+
+        let mut selection = Selection::default();
+        selection.on_focus_move(SelectionMode::Range, cell(3), Modifiers::NONE);
+        selection.on_focus_move(SelectionMode::Range, cell(4), Modifiers::SHIFT);
+        selection.on_focus_move(SelectionMode::Range, cell(5), Modifiers::SHIFT);
+        assert_eq!(ranges(&selection.rows), [(3, 6)]);
+
+        selection.on_focus_move(SelectionMode::Range, cell(2), Modifiers::SHIFT);
+        assert_eq!(ranges(&selection.rows), [(2, 4)]);
+
+        selection.on_focus_move(SelectionMode::Range, cell(7), Modifiers::NONE);
+        assert_eq!(ranges(&selection.rows), [(7, 8)]);
+    }
+}
@@ -0,0 +1,245 @@
+//! Sorting columns by clicking their headers.
+//!
+//! The table only keeps track of _how_ the user wants the rows sorted.
+//! Sorting the actual data is up to you, see [`crate::TableDelegate::on_sort_changed`].
+
+use egui::{Id, Rect, Ui, pos2};
+
+/// Which way to sort a column.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, serde::Deserialize, serde::Serialize)]
+pub enum SortDirection {
+    /// Smallest value first.
+    Ascending,
+
+    /// Largest value first.
+    Descending,
+}
+
+impl SortDirection {
+    /// The opposite direction.
+    #[inline]
+    pub fn reversed(self) -> Self {
+        match self {
+            Self::Ascending => Self::Descending,
+            Self::Descending => Self::Ascending,
+        }
+    }
+
+    /// Is this [`Self::Ascending`]?
+    #[inline]
+    pub fn is_ascending(self) -> bool {
+        self == Self::Ascending
+    }
+}
+
+/// Sort by this column, in this direction.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, serde::Deserialize, serde::Serialize)]
+pub struct SortKey {
+    /// The [`crate::Column::id_for`] of the column.
+    pub column_id: Id,
+
+    pub direction: SortDirection,
+}
+
+/// How the user wants the rows of a [`crate::Table`] sorted.
+///
+/// This is stored in [`crate::TableState::sort`].
+#[derive(Clone, Debug, Default, PartialEq, Eq, serde::Deserialize, serde::Serialize)]
+#[serde(default)]
+pub struct SortState {
+    /// The columns to sort by, most significant first.
+    ///
+    /// Later keys are only used to break ties in the earlier ones.
+    pub keys: Vec<SortKey>,
+}
+
+impl SortState {
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.keys.is_empty()
+    }
+
+    pub fn clear(&mut self) {
+        self.keys.clear();
+    }
+
+    /// Sort by only this one column.
+    pub fn sort_by(&mut self, column_id: Id, direction: SortDirection) {
+        self.keys = vec![SortKey {
+            column_id,
+            direction,
+        }];
+    }
+
+    /// Which way is the given column sorted, if at all?
+    pub fn direction(&self, column_id: Id) -> Option<SortDirection> {
+        self.keys
+            .iter()
+            .find(|key| key.column_id == column_id)
+            .map(|key| key.direction)
+    }
+
+    /// The index of the given column in [`Self::keys`], if it is sorted.
+    ///
+    /// 0 is the most significant column.
+    pub fn priority(&self, column_id: Id) -> Option<usize> {
+        self.keys.iter().position(|key| key.column_id == column_id)
+    }
+
+    /// Update the sort as a response to the user clicking a column header.
+    ///
+    /// Each click cycles the column through ascending, descending and unsorted.
+    /// Without Shift held, the clicked column becomes the only one sorted.
+    /// With Shift held, the clicked column is added to (or cycled within) the current sort.
+    pub fn on_click(&mut self, column_id: Id, modifiers: egui::Modifiers) {
+        let next = match self.direction(column_id) {
+            None => Some(SortDirection::Ascending),
+            Some(SortDirection::Ascending) => Some(SortDirection::Descending),
+            Some(SortDirection::Descending) => None,
+        };
+
+        if modifiers.shift {
+            match (self.priority(column_id), next) {
+                (Some(index), Some(direction)) => self.keys[index].direction = direction,
+                (Some(index), None) => {
+                    self.keys.remove(index);
+                }
+                (None, Some(direction)) => self.keys.push(SortKey {
+                    column_id,
+                    direction,
+                }),
+                (None, None) => {}
+            }
+        } else if let Some(direction) = next {
+            self.sort_by(column_id, direction);
+        } else {
+            self.clear();
+        }
+    }
+}
+
+/// Paint a small arrow at the right side of a header cell [`Ui`], showing which way the column is sorted.
+///
+/// `priority` is the index into [`SortState::keys`].
+/// When sorting by more than one column, the priority is shown next to the arrow.
+///
+/// This is the default implementation of [`crate::TableDelegate::sort_indicator_ui`].
+pub fn paint_sort_indicator(
+    ui: &Ui,
+    direction: SortDirection,
+    priority: usize,
+    num_sort_keys: usize,
+) {
+    let size = 8.0;
+    let margin = 4.0;
+    let color = ui.visuals().strong_text_color();
+    let cell_rect = ui.clip_rect();
+
+    let center = pos2(
+        cell_rect.right() - margin - 0.5 * size,
+        cell_rect.center().y,
+    );
+    let arrow_rect = Rect::from_center_size(center, egui::Vec2::splat(size));
+
+    let (tip, base_y) = if direction.is_ascending() {
+        (arrow_rect.center_top(), arrow_rect.bottom())
+    } else {
+        (arrow_rect.center_bottom(), arrow_rect.top())
+    };
+    ui.painter().add(egui::Shape::convex_polygon(
+        vec![
+            tip,
+            pos2(arrow_rect.left(), base_y),
+            pos2(arrow_rect.right(), base_y),
+        ],
+        color,
+        egui::Stroke::NONE,
+    ));
+
+    if 1 < num_sort_keys {
+        ui.painter().text(
+            pos2(arrow_rect.left() - 2.0, center.y),
+            egui::Align2::RIGHT_CENTER,
+            (priority + 1).to_string(),
+            egui::TextStyle::Small.resolve(ui.style()),
+            color,
+        );
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use egui::Modifiers;
+
+    use super::*;
+
+    fn keys(sort: &SortState) -> Vec<(&'static str, SortDirection)> {
+        let names = ["a", "b", "c"];
+        sort.keys
+            .iter()
+            .map(|key| {
+                let name = names
+                    .iter()
+                    .find(|name| Id::new(name) == key.column_id)
+                    .expect("Unknown column");
+                (*name, key.direction)
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_sort_cycle() {
+        use SortDirection::{Ascending, Descending};
+
+        let a = Id::new("a");
+        let b = Id::new("b");
+
+        let mut sort = SortState::default();
+        sort.on_click(a, Modifiers::NONE);
+        assert_eq!(keys(&sort), [("a", Ascending)]);
+        sort.on_click(a, Modifiers::NONE);
+        assert_eq!(keys(&sort), [("a", Descending)]);
+        sort.on_click(a, Modifiers::NONE);
+        assert!(sort.is_empty());
+
+        sort.on_click(a, Modifiers::NONE);
+        sort.on_click(b, Modifiers::NONE);
+        assert_eq!(
+            keys(&sort),
+            [("b", Ascending)],
+            "Plain click replaces the sort"
+        );
+    }
+
+    #[test]
+    fn test_multi_sort() {
+        use SortDirection::{Ascending, Descending};
+
+        let a = Id::new("a");
+        let b = Id::new("b");
+        let c = Id::new("c");
+
+        let mut sort = SortState::default();
+        sort.on_click(a, Modifiers::NONE);
+        sort.on_click(b, Modifiers::SHIFT);
+        sort.on_click(c, Modifiers::SHIFT);
+        assert_eq!(
+            keys(&sort),
+            [("a", Ascending), ("b", Ascending), ("c", Ascending)]
+        );
+        assert_eq!(sort.priority(c), Some(2));
+
+        sort.on_click(b, Modifiers::SHIFT);
+        assert_eq!(
+            keys(&sort),
+            [("a", Ascending), ("b", Descending), ("c", Ascending)],
+            "Shift-click cycles in place"
+        );
+
+        sort.on_click(b, Modifiers::SHIFT);
+        assert_eq!(keys(&sort), [("a", Ascending), ("c", Ascending)]);
+
+        sort.on_click(c, Modifiers::NONE);
+        assert_eq!(keys(&sort), [("c", Descending)]);
+    }
+}
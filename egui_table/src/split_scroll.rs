@@ -1,43 +1,64 @@
-use egui::{Rect, Ui, UiBuilder, Vec2, Vec2b, pos2, vec2};
-/// A scroll area with some portion of its left and/or top side "stuck".
+use egui::{Id, Rect, Ui, UiBuilder, Vec2, Vec2b, pos2, vec2};
+/// A scroll area with some portion of its left, top, right and/or bottom side "stuck".
 ///
-/// This produces four quadrants:
+/// This produces four quadrants, plus an optional fixed region on the right
+/// and an optional fixed band at the bottom (not shown):
 ///
 /// ```text
-///               <-------LEFT-------> <---------RIGHT---------->
+///               <-------LEFT-------> <---------RIGHT----------> <--FIXED RIGHT-->
 ///
-///              ------------------------------------------------
-///          ^   |                    |   <----------------->   |
-///    TOP   |   |       Fixed        |      Horizontally       |
-///          V   |    fixed_size      |       scrollable        |
-///              |--------------------|-------------------------|.................
-///          ^   | ^                  |           ^             |                .
-///  BOTTOM  |   | |   Vertically     | <-  Fully scrollable -> |                .
-///          |   | |   scrollable     |    scroll_outer_size    |                .
-///          V   | v                  |           v             |                .
-///              |____________________|_________________________|                .
-///                                   .                                          .
-///                                   .                   scroll_content_size    .
-///                                   .                                          .
-///                                   ............................................
+///              ------------------------------------------------------------------
+///          ^   |                    |   <----------------->   |                 |
+///    TOP   |   |       Fixed        |      Horizontally       |      Fixed      |
+///          V   |    fixed_size      |       scrollable        |                 |
+///              |--------------------|-------------------------|-----------------|
+///          ^   | ^                  |           ^             | ^               |
+///  BOTTOM  |   | |   Vertically     | <-  Fully scrollable -> | |  Vertically   |
+///          |   | |   scrollable     |    scroll_outer_size    | |  scrollable   |
+///          V   | v                  |           v             | v               |
+///              |____________________|_________________________|_________________|
+///                                   .                          fixed_right_width
+///                                   .
+///                                   .    scroll_content_size extends beyond the
+///                                   .    fully scrollable region, to the right and down.
 /// ```
 ///
 /// The above shows the initial layout when the scroll offset is zero (no scrolling has occurred yet).
+///
+/// Create it with [`Self::new`], since more fields may be added.
 #[derive(Clone, Copy, Debug)]
+#[non_exhaustive]
 pub struct SplitScroll {
     pub scroll_enabled: Vec2b,
 
     /// Width of the fixed left side, and height of the fixed top.
     pub fixed_size: Vec2,
 
+    /// Width of the fixed right side.
+    ///
+    /// If zero, there is no fixed right side.
+    pub fixed_right_width: f32,
+
+    /// Height of the fixed band at the bottom, which only scrolls horizontally.
+    ///
+    /// If zero, there is no fixed bottom band.
+    pub fixed_bottom_height: f32,
+
     /// Size of the small container of the right bottom scrollable region.
     pub scroll_outer_size: Vec2,
 
-    /// Size of the large contents of the right bottom region, ignoring the left/top fixed regions.
+    /// Size of the large contents of the right bottom region, ignoring the fixed regions.
     pub scroll_content_size: Vec2,
 
     /// If true, the vertical scrollbar will stick to the bottom as the content grows.
     pub stick_to_bottom: bool,
+
+    /// Scroll down by this much before showing the contents.
+    ///
+    /// Unlike setting the scroll offset, this keeps any scrolling the user did last frame.
+    /// Use this to keep the view in place when the contents change above it,
+    /// or to scroll through contents too tall for `f32`, by moving a window over it.
+    pub vertical_scroll_shift: f32,
 }
 
 /// The contents of a [`SplitScroll`].
@@ -56,29 +77,80 @@ pub trait SplitScrollDelegate {
     /// First to be called.
     fn right_bottom_ui(&mut self, ui: &mut Ui);
 
+    /// The fixed portion of the top right corner.
+    ///
+    /// Only called if [`SplitScroll::fixed_right_width`] is positive.
+    fn fixed_right_top_ui(&mut self, _ui: &mut Ui) {}
+
+    /// The vertically scrollable portion on the right side.
+    ///
+    /// Only called if [`SplitScroll::fixed_right_width`] is positive.
+    fn fixed_right_bottom_ui(&mut self, _ui: &mut Ui) {}
+
+    /// The fixed portion of the bottom left corner.
+    ///
+    /// Only called if [`SplitScroll::fixed_bottom_height`] is positive.
+    fn left_footer_ui(&mut self, _ui: &mut Ui) {}
+
+    /// The horizontally scrollable portion of the bottom band.
+    ///
+    /// Only called if [`SplitScroll::fixed_bottom_height`] is positive.
+    fn right_footer_ui(&mut self, _ui: &mut Ui) {}
+
+    /// The fixed portion of the bottom right corner.
+    ///
+    /// Only called if both [`SplitScroll::fixed_right_width`]
+    /// and [`SplitScroll::fixed_bottom_height`] are positive.
+    fn fixed_right_footer_ui(&mut self, _ui: &mut Ui) {}
+
     /// Called last.
     fn finish(&mut self, _ui: &mut Ui) {}
 }
 
 impl SplitScroll {
+    /// Scrollable in both directions, with no fixed right side or bottom band.
+    pub fn new(fixed_size: Vec2, scroll_outer_size: Vec2, scroll_content_size: Vec2) -> Self {
+        Self {
+            scroll_enabled: Vec2b::new(true, true),
+            fixed_size,
+            fixed_right_width: 0.0,
+            fixed_bottom_height: 0.0,
+            scroll_outer_size,
+            scroll_content_size,
+            stick_to_bottom: false,
+            vertical_scroll_shift: 0.0,
+        }
+    }
+
     pub fn show(self, ui: &mut Ui, delegate: &mut dyn SplitScrollDelegate) {
         let Self {
             scroll_enabled,
             fixed_size,
+            fixed_right_width,
+            fixed_bottom_height,
             scroll_outer_size,
             scroll_content_size,
             stick_to_bottom,
+            vertical_scroll_shift,
         } = self;
+        let fixed_right_width = fixed_right_width.max(0.0);
+        let fixed_bottom_height = fixed_bottom_height.max(0.0);
+        let fixed_far_size = vec2(fixed_right_width, fixed_bottom_height);
 
         ui.scope(|ui| {
             ui.visuals_mut().clip_rect_margin = 0.0; // Everything else looks awful
 
             let mut rect = ui.cursor();
-            rect.max = rect.min + fixed_size + scroll_outer_size;
+            rect.max = rect.min + fixed_size + scroll_outer_size + fixed_far_size;
             ui.shrink_clip_rect(rect);
             let rect = rect;
 
-            let bottom_right_rect = Rect::from_min_max(rect.min + fixed_size, rect.max);
+            // Where the fixed right side and the fixed bottom band start:
+            let fixed_right_x = rect.right() - fixed_right_width;
+            let fixed_bottom_y = rect.bottom() - fixed_bottom_height;
+
+            let bottom_right_rect =
+                Rect::from_min_max(rect.min + fixed_size, pos2(fixed_right_x, fixed_bottom_y));
 
             let scroll_offset = {
                 // RIGHT BOTTOM: fully scrollable.
@@ -89,12 +161,25 @@ impl SplitScroll {
 
                 let mut scroll_ui = ui.new_child(UiBuilder::new().max_rect(rect));
 
-                egui::ScrollArea::new(scroll_enabled)
+                let scroll_area_salt = "scroll_area";
+                let scroll_area_id = scroll_ui.make_persistent_id(Id::new(scroll_area_salt));
+                if vertical_scroll_shift != 0.0
+                    && let Some(mut state) =
+                        egui::scroll_area::State::load(ui.ctx(), scroll_area_id)
+                {
+                    state.offset.y += vertical_scroll_shift;
+                    state.store(ui.ctx(), scroll_area_id);
+                }
+
+                let output = egui::ScrollArea::new(scroll_enabled)
+                    .id_salt(scroll_area_salt)
                     .auto_shrink(false)
                     .scroll_bar_rect(bottom_right_rect)
                     .stick_to_bottom(stick_to_bottom)
                     .show_viewport(&mut scroll_ui, |ui, scroll_offset| {
-                        ui.set_min_size(fixed_size + scroll_content_size);
+                        // The fixed right side and bottom band cover the end of the content,
+                        // so make room for them:
+                        ui.set_min_size(fixed_size + scroll_content_size + fixed_far_size);
 
                         let mut shrunk_rect = ui.max_rect();
                         shrunk_rect.min += fixed_size;
@@ -109,8 +194,12 @@ impl SplitScroll {
                         // The scroll offset that `ScrollArea` returns could be a newer one
                         // than was used for rendering, so we use the one _actually_ used for rendering instead:
                         scroll_offset.min
-                    })
-                    .inner
+                    });
+                debug_assert_eq!(
+                    output.id, scroll_area_id,
+                    "The scroll shift must be applied to the state of this scroll area"
+                );
+                output.inner
             };
 
             {
@@ -127,6 +216,7 @@ impl SplitScroll {
                 // RIGHT TOP: Horizontally scrollable
                 let right_top_outer_rect = rect
                     .with_min_x(rect.left() + fixed_size.x)
+                    .with_max_x(fixed_right_x)
                     .with_max_y(rect.top() + fixed_size.y);
                 let right_top_content_rect = Rect::from_min_size(
                     pos2(right_top_outer_rect.min.x - scroll_offset.x, rect.min.y),
@@ -142,7 +232,8 @@ impl SplitScroll {
                 // LEFT BOTTOM: Vertically scrollable
                 let left_bottom_outer_rect = rect
                     .with_max_x(rect.left() + fixed_size.x)
-                    .with_min_y(rect.top() + fixed_size.y);
+                    .with_min_y(rect.top() + fixed_size.y)
+                    .with_max_y(fixed_bottom_y);
                 let left_bottom_content_rect = Rect::from_min_size(
                     pos2(rect.min.x, left_bottom_outer_rect.min.y - scroll_offset.y),
                     vec2(fixed_size.x, scroll_content_size.y),
@@ -153,6 +244,79 @@ impl SplitScroll {
                 delegate.left_bottom_ui(&mut left_bottom_ui);
             }
 
+            if 0.0 < fixed_right_width {
+                {
+                    // FIXED RIGHT TOP: Fixed
+                    let right_top_rect = rect
+                        .with_min_x(fixed_right_x)
+                        .with_max_y(rect.top() + fixed_size.y);
+                    let mut right_top_ui = ui.new_child(UiBuilder::new().max_rect(right_top_rect));
+                    right_top_ui.shrink_clip_rect(right_top_rect);
+                    delegate.fixed_right_top_ui(&mut right_top_ui);
+                }
+
+                {
+                    // FIXED RIGHT BOTTOM: Vertically scrollable
+                    let right_bottom_outer_rect = rect
+                        .with_min_x(fixed_right_x)
+                        .with_min_y(rect.top() + fixed_size.y)
+                        .with_max_y(fixed_bottom_y);
+                    let right_bottom_content_rect = Rect::from_min_size(
+                        pos2(
+                            fixed_right_x,
+                            right_bottom_outer_rect.min.y - scroll_offset.y,
+                        ),
+                        vec2(fixed_right_width, scroll_content_size.y),
+                    );
+                    let mut right_bottom_ui =
+                        ui.new_child(UiBuilder::new().max_rect(right_bottom_content_rect));
+                    right_bottom_ui.shrink_clip_rect(right_bottom_outer_rect);
+                    delegate.fixed_right_bottom_ui(&mut right_bottom_ui);
+                }
+            }
+
+            if 0.0 < fixed_bottom_height {
+                {
+                    // LEFT FOOTER: Fixed
+                    let left_footer_rect = rect
+                        .with_max_x(rect.left() + fixed_size.x)
+                        .with_min_y(fixed_bottom_y);
+                    let mut left_footer_ui =
+                        ui.new_child(UiBuilder::new().max_rect(left_footer_rect));
+                    left_footer_ui.shrink_clip_rect(left_footer_rect);
+                    delegate.left_footer_ui(&mut left_footer_ui);
+                }
+
+                {
+                    // RIGHT FOOTER: Horizontally scrollable
+                    let right_footer_outer_rect = rect
+                        .with_min_x(rect.left() + fixed_size.x)
+                        .with_max_x(fixed_right_x)
+                        .with_min_y(fixed_bottom_y);
+                    let right_footer_content_rect = Rect::from_min_size(
+                        pos2(
+                            right_footer_outer_rect.min.x - scroll_offset.x,
+                            fixed_bottom_y,
+                        ),
+                        vec2(scroll_content_size.x, fixed_bottom_height),
+                    );
+                    let mut right_footer_ui =
+                        ui.new_child(UiBuilder::new().max_rect(right_footer_content_rect));
+                    right_footer_ui.shrink_clip_rect(right_footer_outer_rect);
+                    delegate.right_footer_ui(&mut right_footer_ui);
+                }
+
+                if 0.0 < fixed_right_width {
+                    // FIXED RIGHT FOOTER: Fixed
+                    let right_footer_rect =
+                        rect.with_min_x(fixed_right_x).with_min_y(fixed_bottom_y);
+                    let mut right_footer_ui =
+                        ui.new_child(UiBuilder::new().max_rect(right_footer_rect));
+                    right_footer_ui.shrink_clip_rect(right_footer_rect);
+                    delegate.fixed_right_footer_ui(&mut right_footer_ui);
+                }
+            }
+
             delegate.finish(ui);
             ui.advance_cursor_after_rect(rect);
         });
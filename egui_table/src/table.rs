@@ -1,15 +1,61 @@
 use std::{
-    collections::{BTreeMap, btree_map::Entry},
+    collections::{BTreeMap, HashMap, HashSet, btree_map::Entry},
+    io,
     ops::{Range, RangeInclusive},
+    sync::{Arc, Weak},
 };
 
 use egui::{
-    Align, Context, Id, IdMap, Layout, NumExt as _, Rangef, Rect, Response, Ui, UiBuilder, Vec2,
-    Vec2b, vec2,
+    Align, Context, Id, IdMap, Key, Layout, Modifiers, NumExt as _, Pos2, Rangef, Rect, Response,
+    Sense, Ui, UiBuilder, Vec2, Vec2b, vec2,
 };
 use vec1::Vec1;
 
-use crate::{SplitScroll, SplitScrollDelegate, columns::Column};
+use crate::{
+    ColumnFilter, FilterState, FilterValue, RowHeights, RowMapping, SelectionMode, SortDirection,
+    SortState, SplitScroll, SplitScrollDelegate, TextFormat,
+    columns::{Column, column_order, groups_are_contiguous, move_column},
+    export::{HeaderText, TableWriter},
+    navigation::{NAVIGATION_KEYS, move_focus},
+    row_heights::to_f32,
+    selection::{CellCoord, CellRange, CellSelection, RowSet, Selection},
+    tree::{FlatTree, TreeNode},
+};
+
+/// The scroll area of the body is never taller than this.
+///
+/// `f32` loses sub-pixel precision for larger offsets.
+/// Taller tables only scroll through a window of this height,
+/// which is moved along as the user scrolls.
+/// See [`TableState::scroll_origin`].
+const MAX_SCROLL_HEIGHT: f64 = 1_000_000.0;
+
+/// How many rows away from where we expect it we look for the row the view is anchored to.
+///
+/// See [`TableDelegate::row_id`].
+const MAX_SCROLL_ANCHOR_DISTANCE: u64 = 10_000;
+
+/// Only this many selected rows follow their [`TableDelegate::row_id`] when the rows move.
+///
+/// Larger selections stay with their row numbers.
+const MAX_TRACKED_SELECTED_ROWS: u64 = 10_000;
+
+/// At most this many rows are searched for rows that moved, each frame.
+///
+/// Smaller tables are searched through completely.
+/// See [`TableDelegate::source_row_of_id`].
+const MAX_ROWS_TO_SEARCH: u64 = 100_000;
+
+/// In larger tables, rows that moved are only searched for this many rows away from where they were.
+///
+/// See [`TableDelegate::source_row_of_id`].
+const MAX_ROW_SEARCH_DISTANCE: u64 = 1_000;
+
+/// Ctrl+C copies at most this many rows, see [`Table::copy_format`].
+const MAX_ROWS_TO_COPY: u64 = 100_000;
+
+/// [`Table::export`] writes this many rows after each call to [`TableDelegate::prepare`].
+const EXPORT_CHUNK_SIZE: u64 = 1_000;
 
 // TODO: fix the functionality of this
 #[derive(Clone, Copy, Debug, Default, PartialEq, Eq, serde::Deserialize, serde::Serialize)]
@@ -26,11 +72,120 @@ pub enum AutoSizeMode {
 }
 
 #[derive(Clone, Debug, Default, serde::Deserialize, serde::Serialize)]
+#[serde(default)]
 pub struct TableState {
     // Maps columns ids to their widths.
     pub col_widths: IdMap<f32>,
 
     pub parent_width: Option<f32>,
+
+    /// The order of the columns, as set by the user dragging column headers.
+    ///
+    /// This contains [`Column::id_for`] of each column.
+    /// If empty, the columns are shown in the order they were given to [`Table::columns`].
+    pub col_order: Vec<Id>,
+
+    /// Maps column ids to whether or not they are shown.
+    ///
+    /// Columns missing from this map are shown.
+    /// See [`Self::set_column_visible`] and [`Table::column_chooser`].
+    pub col_visible: IdMap<bool>,
+
+    /// The selected rows.
+    ///
+    /// Only used if [`Table::selection_mode`] is not [`SelectionMode::None`].
+    pub selection: Selection,
+
+    /// The cell with keyboard focus, set by clicking it.
+    ///
+    /// It is moved with the arrow keys if [`Table::keyboard_navigation`] is turned on.
+    /// See [`CellInfo::is_focused`].
+    pub focus: Option<CellCoord>,
+
+    /// Rows pinned to the top of the body, below the header, in the order they were pinned.
+    ///
+    /// These stay visible while the rest of the rows scroll.
+    /// See [`Self::pin_row`] and [`Table::show_pinned_rows_in_body`].
+    pub pinned_rows: Vec<u64>,
+
+    /// How the user wants the rows sorted, set by clicking the headers of [`Column::sortable`] columns.
+    ///
+    /// See [`TableDelegate::on_sort_changed`].
+    pub sort: SortState,
+
+    /// What the user has entered into the [`Table::filter_row`].
+    ///
+    /// See [`TableDelegate::on_filters_changed`].
+    pub filters: FilterState,
+
+    /// The [`TableDelegate::row_id`] of the rows that are expanded, showing their children.
+    ///
+    /// Only used if you have set a [`Table::tree_column`].
+    /// Use [`Self::set_row_expanded`] to change this, or call [`Self::refresh_tree`] afterwards.
+    pub expanded_rows: HashSet<Id>,
+
+    /// The visual rows of the [`Table::tree_column`] tree, as of the last time it changed.
+    #[serde(skip)]
+    pub(crate) flat_tree: Option<FlatTree>,
+
+    /// Bumped whenever a row is expanded or collapsed, so that [`Self::flat_tree`] is rebuilt.
+    #[serde(skip)]
+    pub(crate) tree_generation: u64,
+
+    /// The measured height of each row that has been shown, keyed by visual row.
+    ///
+    /// Only used if [`Table::auto_row_heights`] is turned on.
+    /// Clear this if the contents of the rows change.
+    ///
+    /// The heights move along with their rows when rows are added or removed above them,
+    /// and are measured again when the rows are sorted or filtered, see [`TableDelegate::row_id`].
+    #[serde(skip)]
+    pub row_heights: RowHeights,
+
+    /// The scroll offset of the body, as of the last frame.
+    #[serde(skip)]
+    pub(crate) scroll_offset: Vec2,
+
+    /// How much to scroll down next frame, to keep the rows in view from moving
+    /// when the rows above them change height.
+    #[serde(skip)]
+    pub(crate) scroll_correction: f32,
+
+    /// Where the scroll area starts in the body, for tables taller than [`MAX_SCROLL_HEIGHT`].
+    ///
+    /// This is zero for all other tables.
+    ///
+    /// egui persists the scroll offset of the scroll area, which is relative to this,
+    /// so this is persisted too, to restore the view to the same rows.
+    pub(crate) scroll_origin: f64,
+
+    /// The row at the top of the view, as of the last frame.
+    #[serde(skip)]
+    pub(crate) scroll_anchor: Option<ScrollAnchor>,
+
+    /// The rows that the selection, focus and pinned rows refer to, as of the last frame.
+    #[serde(skip)]
+    pub(crate) tracked_rows: TrackedRows,
+
+    /// Bumped by [`Self::refresh_row_ids`], so that the [`Self::tracked_rows`] are looked for again.
+    #[serde(skip)]
+    pub(crate) row_ids_generation: u64,
+
+    /// Is the user currently dragging to select cells?
+    #[serde(skip)]
+    pub(crate) is_drag_selecting: bool,
+
+    /// The column index at each display position, as of the last frame.
+    #[serde(skip)]
+    pub(crate) display_columns: Vec<usize>,
+
+    /// The rows in view, as of the last frame.
+    #[serde(skip)]
+    pub(crate) visible_rows: Range<u64>,
+
+    /// The cell being edited, if any.
+    #[serde(skip)]
+    pub(crate) editing: Option<CellEdit>,
 }
 
 impl TableState {
@@ -51,6 +206,124 @@ impl TableState {
             d.remove::<Self>(id);
         });
     }
+
+    /// The rectangle of selected cells, if any.
+    ///
+    /// See [`SelectionMode::Cells`].
+    pub fn selected_cells(&self) -> Option<CellRange> {
+        self.selection.cells.map(|cells| cells.range())
+    }
+
+    /// The cell the user is editing, if any.
+    ///
+    /// See [`Column::editable`].
+    pub fn editing_cell(&self) -> Option<CellCoord> {
+        self.editing.as_ref().map(|edit| edit.cell)
+    }
+
+    /// Stop editing, throwing away what the user has typed.
+    pub fn cancel_edit(&mut self) {
+        self.editing = None;
+    }
+
+    /// Is the column with the given [`Column::id_for`] shown?
+    pub fn is_column_visible(&self, column_id: Id) -> bool {
+        self.col_visible.get(&column_id).copied().unwrap_or(true)
+    }
+
+    /// Show or hide the column with the given [`Column::id_for`].
+    pub fn set_column_visible(&mut self, column_id: Id, visible: bool) {
+        if visible {
+            self.col_visible.remove(&column_id);
+        } else {
+            self.col_visible.insert(column_id, false);
+        }
+    }
+
+    #[inline]
+    pub fn is_row_pinned(&self, row_nr: u64) -> bool {
+        self.pinned_rows.contains(&row_nr)
+    }
+
+    /// Pin a row to the top of the body, below any already pinned rows.
+    pub fn pin_row(&mut self, row_nr: u64) {
+        if !self.is_row_pinned(row_nr) {
+            self.pinned_rows.push(row_nr);
+        }
+    }
+
+    pub fn unpin_row(&mut self, row_nr: u64) {
+        self.pinned_rows.retain(|&pinned| pinned != row_nr);
+    }
+
+    /// Is the row with the given [`TableDelegate::row_id`] expanded, showing its children?
+    ///
+    /// See [`Table::tree_column`].
+    #[inline]
+    pub fn is_row_expanded(&self, row_id: Id) -> bool {
+        self.expanded_rows.contains(&row_id)
+    }
+
+    pub fn set_row_expanded(&mut self, row_id: Id, expanded: bool) {
+        let changed = if expanded {
+            self.expanded_rows.insert(row_id)
+        } else {
+            self.expanded_rows.remove(&row_id)
+        };
+        if changed {
+            self.refresh_tree();
+        }
+    }
+
+    /// Find the visible rows of the [`Table::tree_column`] tree again next frame.
+    ///
+    /// They are only found again by themselves when [`Table::num_rows`] changes,
+    /// or when rows are expanded or collapsed with [`Self::set_row_expanded`].
+    /// Call this if your tree changed in some other way, e.g. [`TableDelegate::tree_node`]
+    /// or [`TableDelegate::row_id`] changed for some rows, or [`Self::expanded_rows`] was changed directly.
+    pub fn refresh_tree(&mut self) {
+        self.tree_generation += 1;
+    }
+
+    /// Check next frame whether the rows of the selection, focus and pinned rows have moved.
+    ///
+    /// This is only checked by itself when [`Table::num_rows`] or [`Table::row_mapping`] changes.
+    /// Call this if your rows moved in some other way, e.g. when you sort your data yourself.
+    /// See [`TableDelegate::row_id`].
+    pub fn refresh_row_ids(&mut self) {
+        self.row_ids_generation += 1;
+    }
+
+    /// The index of the column shown at the given display position.
+    ///
+    /// These differ if the user has reordered or hidden columns.
+    pub fn column_at(&self, col_pos: usize) -> Option<usize> {
+        self.display_columns.get(col_pos).copied()
+    }
+
+    /// Keep the focus, the edited cell and the corners of the selected cells in their columns
+    /// when the columns are shown in a new order.
+    fn follow_moved_columns(
+        &mut self,
+        old_display_columns: &[usize],
+        new_display_columns: &[usize],
+    ) {
+        let new_cell = |cell: CellCoord| {
+            let col_pos = old_display_columns
+                .get(cell.col_pos)
+                .and_then(|col_nr| new_display_columns.iter().position(|c| c == col_nr))
+                .unwrap_or(cell.col_pos);
+            CellCoord { col_pos, ..cell }
+        };
+        self.focus = self.focus.map(new_cell);
+        if let Some(edit) = &mut self.editing {
+            edit.cell = new_cell(edit.cell);
+        }
+        if let Some(cells) = &mut self.selection.cells {
+            cells.anchor = new_cell(cells.anchor);
+            cells.focus = new_cell(cells.focus);
+        }
+    }
 }
 
 /// Describes one of potentially many header rows.
@@ -76,6 +349,31 @@ impl HeaderRow {
     }
 }
 
+/// Describes one of potentially many footer rows, e.g. for totals.
+///
+/// The footer rows are stuck to the bottom of the table,
+/// and only scroll horizontally.
+/// Each footer row has a fixed height.
+#[derive(Clone, Debug, serde::Deserialize, serde::Serialize)]
+pub struct FooterRow {
+    pub height: f32,
+
+    /// If empty, it is ignored.
+    ///
+    /// Contains non-overlapping ranges of column indices to group together,
+    /// just like [`HeaderRow::groups`].
+    pub groups: Vec<Range<usize>>,
+}
+
+impl FooterRow {
+    pub fn new(height: f32) -> Self {
+        Self {
+            height,
+            groups: Default::default(),
+        }
+    }
+}
+
 /// A table viewer.
 ///
 /// Designed to be fast when there are millions of rows, but only hundreds of columns.
@@ -85,9 +383,10 @@ impl HeaderRow {
 /// These won't scroll with the rest of the table.
 ///
 /// The sticky rows are always the first ones at the top, and are usually used for the column headers.
-/// The sticky columns are always the first ones on the left, useful for special columns like
+/// The sticky columns are the first ones on the left, useful for special columns like
 /// table row number or similar.
 /// A sticky column is sometimes called a "gutter".
+/// You can also make the last columns sticky, to keep e.g. a status column pinned to the right edge.
 ///
 /// ## Batteries not included
 /// * You need to specify the `Table` size beforehand
@@ -107,15 +406,54 @@ pub struct Table {
     /// Which columns are sticky (non-scrolling)?
     num_sticky_cols: usize,
 
+    /// How many of the last columns are sticky (non-scrolling), pinned to the right edge?
+    num_sticky_cols_right: usize,
+
     /// The count and parameters of the sticky (non-scrolling) header rows.
     headers: Vec<HeaderRow>,
 
+    /// The count and parameters of the footer rows, stuck to the bottom.
+    footers: Vec<FooterRow>,
+
     /// Total number of rows (sticky + non-sticky).
     num_rows: u64,
 
+    /// Which source row is shown at each visual row, if not the same.
+    row_mapping: Option<Arc<RowMapping>>,
+
+    /// Show the rows as a tree, with the disclosure triangles in this column.
+    tree_column: Option<usize>,
+
+    /// Measure the height of the rows, instead of asking [`TableDelegate::row_top_offset_f64`].
+    auto_row_heights: bool,
+
+    /// The measured heights of the rows, taken from [`TableState::row_heights`] in [`Self::show`].
+    row_heights: RowHeights,
+
+    /// Taken from [`TableState::scroll_origin`] in [`Self::show`].
+    scroll_origin: f64,
+
+    /// The rows with a full-width detail panel below them.
+    detail_rows: Vec<u64>,
+
+    /// The rows with a detail panel, sorted, with the height of the panel
+    /// and the total height of all the panels above it.
+    ///
+    /// Set from [`Self::detail_rows`] at the start of [`Self::show`].
+    details: Vec<(u64, f32, f64)>,
+
+    /// Also show the pinned rows at their usual place in the body?
+    show_pinned_rows_in_body: bool,
+
+    /// The pinned rows that exist, and their heights.
+    ///
+    /// Set from [`TableState::pinned_rows`] at the start of [`Self::show`].
+    pinned_rows: Vec<(u64, f32)>,
+
     /// How to do auto-sizing of columns, if at all.
     auto_size_mode: AutoSizeMode,
 
+    /// Column indices, converted to display positions at the start of [`Self::show`].
     scroll_to_columns: Option<(RangeInclusive<usize>, Option<Align>)>,
     scroll_to_rows: Option<(RangeInclusive<u64>, Option<Align>)>,
 
@@ -123,6 +461,21 @@ pub struct Table {
     ///
     /// Useful for log views or terminal emulation.
     stick_to_bottom: bool,
+
+    /// How the user can select rows.
+    selection_mode: SelectionMode,
+
+    /// Move the focused cell with the arrow keys?
+    keyboard_navigation: bool,
+
+    /// Show a column chooser when right-clicking a header cell?
+    column_chooser: bool,
+
+    /// How to copy cells to the clipboard.
+    copy_format: TextFormat,
+
+    /// The height of the row of filter widgets below the header, if shown.
+    filter_row: Option<f32>,
 }
 
 impl Default for Table {
@@ -131,23 +484,93 @@ impl Default for Table {
             columns: vec![],
             id_salt: Id::new("table"),
             num_sticky_cols: 0,
+            num_sticky_cols_right: 0,
             headers: vec![HeaderRow::new(16.0)],
+            footers: vec![],
+            show_pinned_rows_in_body: true,
+            pinned_rows: vec![],
             num_rows: 0,
+            row_mapping: None,
+            tree_column: None,
+            auto_row_heights: false,
+            row_heights: Default::default(),
+            scroll_origin: 0.0,
+            detail_rows: vec![],
+            details: vec![],
             auto_size_mode: AutoSizeMode::default(),
             scroll_to_columns: None,
             scroll_to_rows: None,
             stick_to_bottom: false,
+            selection_mode: SelectionMode::default(),
+            keyboard_navigation: false,
+            column_chooser: false,
+            copy_format: TextFormat::default(),
+            filter_row: None,
         }
     }
 }
 
+/// What happened when a [`Table`] was shown.
+#[derive(Clone, Debug)]
+#[non_exhaustive]
+pub struct TableResponse {
+    /// The response of the whole table area.
+    pub response: Response,
+
+    /// Did the user change the selection this frame?
+    ///
+    /// You can read the new selection with [`TableState::load`].
+    pub selection_changed: bool,
+}
+
 #[derive(Clone, Debug)]
 #[non_exhaustive]
 pub struct CellInfo {
+    /// The column index, i.e. the index into [`Table::columns`].
     pub col_nr: usize,
 
+    /// Where the column is currently shown, counting from the left.
+    ///
+    /// This is different from [`Self::col_nr`] if the user has reordered the columns.
+    pub col_pos: usize,
+
+    /// The display positions covered by this cell, starting with [`Self::col_pos`].
+    ///
+    /// This is more than one column if the cell spans several, see [`TableDelegate::cell_span`].
+    pub col_pos_range: Range<usize>,
+
+    /// The visual row, counting from the top of the body.
+    ///
+    /// This is what [`TableDelegate::row_top_offset_f64`], [`PrefetchInfo::visible_rows`],
+    /// and the selection all use.
     pub row_nr: u64,
 
+    /// The visual rows covered by this cell, starting with [`Self::row_nr`].
+    ///
+    /// This is more than one row if the cell spans several, see [`TableDelegate::cell_row_span`].
+    pub row_range: Range<u64>,
+
+    /// Is this one of the [`TableState::pinned_rows`], shown below the header?
+    ///
+    /// A pinned row is also shown in the body, with this set to `false`,
+    /// unless [`Table::show_pinned_rows_in_body`] is turned off.
+    pub is_pinned: bool,
+
+    /// Is this a section header stuck to the top of the body?
+    ///
+    /// See [`TableDelegate::section_header_row`].
+    pub is_sticky_section_header: bool,
+
+    /// The row of your data source shown in this row.
+    ///
+    /// This is the same as [`Self::row_nr`] unless you have set a [`Table::row_mapping`].
+    pub source_row: u64,
+
+    /// Is this the focused cell, i.e. the one moved with the arrow keys?
+    ///
+    /// See [`TableState::focus`].
+    pub is_focused: bool,
+
     /// The unique [`Id`] of this table.
     pub table_id: Id,
     // We could add more stuff here, like a reference to the column
@@ -158,11 +581,43 @@ pub struct CellInfo {
 pub struct HeaderCellInfo {
     pub group_index: usize,
 
+    /// The column indices of this header cell, i.e. indices into [`Table::columns`].
     pub col_range: Range<usize>,
 
+    /// Where the columns of this header cell are currently shown, counting from the left.
+    ///
+    /// This is different from [`Self::col_range`] if the user has reordered the columns.
+    pub col_pos_range: Range<usize>,
+
     /// Header row
     pub row_nr: usize,
 
+    /// Which way this column is sorted, if at all.
+    ///
+    /// Only set for single-column header cells in the last header row.
+    /// See [`TableState::sort`].
+    pub sort_direction: Option<SortDirection>,
+
+    /// The unique [`Id`] of this table.
+    pub table_id: Id,
+}
+
+#[derive(Clone, Debug)]
+#[non_exhaustive]
+pub struct FooterCellInfo {
+    pub group_index: usize,
+
+    /// The column indices of this footer cell, i.e. indices into [`Table::columns`].
+    pub col_range: Range<usize>,
+
+    /// Where the columns of this footer cell are currently shown, counting from the left.
+    ///
+    /// This is different from [`Self::col_range`] if the user has reordered the columns.
+    pub col_pos_range: Range<usize>,
+
+    /// Footer row, counting from the top of the footer.
+    pub row_nr: usize,
+
     /// The unique [`Id`] of this table.
     pub table_id: Id,
 }
@@ -171,19 +626,68 @@ pub struct HeaderCellInfo {
 #[derive(Clone, Debug, serde::Deserialize, serde::Serialize)]
 #[non_exhaustive]
 pub struct PrefetchInfo {
-    /// The sticky columns are always visible.
+    /// The sticky columns on the left are always visible.
     pub num_sticky_columns: usize,
 
+    /// The sticky columns on the right are always visible.
+    ///
+    /// These are the last display positions.
+    pub num_sticky_columns_right: usize,
+
     /// This range of columns are currently visible, in addition to the sticky ones.
+    ///
+    /// Note that this may include some of the sticky columns.
+    ///
+    /// These are display positions, which skip hidden columns.
+    /// Use [`Self::column_order`] or [`Self::visible_column_nrs`] to get the column indices.
     pub visible_columns: Range<usize>,
 
-    /// These rows are currently visible.
+    /// The column index shown at each display position.
+    ///
+    /// Hidden columns are not included.
+    pub column_order: Vec<usize>,
+
+    /// These rows are currently visible in the body.
+    ///
+    /// These are visual rows.
+    /// A cell spanning several rows (see [`TableDelegate::cell_row_span`]) may start above them.
+    /// If you use a [`Table::row_mapping`], use [`RowMapping::source_rows`] to get the source rows.
     pub visible_rows: Range<u64>,
 
+    /// These rows are pinned below the header, so they are also visible.
+    ///
+    /// See [`TableState::pinned_rows`].
+    pub pinned_rows: Vec<u64>,
+
+    /// This section header row is stuck to the top of the body, so it is also visible.
+    ///
+    /// See [`TableDelegate::section_header_row`].
+    pub sticky_section_header: Option<u64>,
+
+    /// This cell is being edited, so it is shown even when it is out of view.
+    ///
+    /// See [`TableState::editing_cell`].
+    pub editing_cell: Option<CellCoord>,
+
     /// The unique [`Id`] of this table.
     pub table_id: Id,
 }
 
+impl PrefetchInfo {
+    /// The indices of all visible columns, including the sticky ones, in display order.
+    pub fn visible_column_nrs(&self) -> impl Iterator<Item = usize> + '_ {
+        let num_columns = self.column_order.len();
+        let sticky_left = 0..self.num_sticky_columns.min(self.visible_columns.start);
+        let sticky_right = num_columns
+            .saturating_sub(self.num_sticky_columns_right)
+            .max(self.visible_columns.end)..num_columns;
+        sticky_left
+            .chain(self.visible_columns.clone())
+            .chain(sticky_right)
+            .filter_map(|col_pos| self.column_order.get(col_pos).copied())
+    }
+}
+
 /// The interface that the user needs to implement to display a table.
 ///
 /// The [`Table`] calls functions on the delegate to render the table.
@@ -191,6 +695,9 @@ pub trait TableDelegate {
     /// Called before any call to [`Self::cell_ui`] to communicate the range of visible columns and rows.
     ///
     /// You can use this to only load the data required to be viewed.
+    ///
+    /// [`Table::export`] calls this for each chunk of rows it writes,
+    /// with all the shown columns and the rows of the chunk.
     fn prepare(&mut self, _info: &PrefetchInfo) {}
 
     /// The contents of a header cell in the table.
@@ -198,6 +705,15 @@ pub trait TableDelegate {
     /// The [`CellInfo::row_nr`] is which header row (usually 0).
     fn header_cell_ui(&mut self, ui: &mut Ui, cell: &HeaderCellInfo);
 
+    /// The contents of a footer cell in the table, e.g. a total.
+    ///
+    /// The [`FooterCellInfo::row_nr`] is which footer row.
+    /// Only called if you have set [`Table::footers`].
+    ///
+    /// This is called once per cell, except for cells that straddle the edge of the sticky columns,
+    /// which are shown on both sides of it.
+    fn footer_cell_ui(&mut self, _ui: &mut Ui, _cell: &FooterCellInfo) {}
+
     /// The contents of a row.
     ///
     /// Individual cell [`Ui`]s will be children of the ui passed to this fn, so you can e.g. use
@@ -211,80 +727,416 @@ pub trait TableDelegate {
     /// The [`CellInfo::row_nr`] is ignoring header rows.
     fn cell_ui(&mut self, ui: &mut Ui, cell: &CellInfo);
 
+    /// Which columns the body cell at the given row and column spans, as a range of column indices.
+    ///
+    /// Return a range containing `col_nr` to merge the cell with its neighbors,
+    /// like [`HeaderRow::groups`] does for the header.
+    /// [`Self::cell_ui`] is then called once for the whole span, see [`CellInfo::col_pos_range`].
+    /// Spans are cut at the sticky columns, so that each side gets its own cell.
+    ///
+    /// The default implementation returns `col_nr..col_nr + 1`, i.e. no spanning.
+    fn cell_span(&self, _row_nr: u64, col_nr: usize) -> Range<usize> {
+        col_nr..col_nr + 1
+    }
+
+    /// Which rows the body cell at the given row and column spans, as a range of visual rows.
+    ///
+    /// Return a range containing `row_nr` to merge the cell with the ones above and/or below it.
+    /// All the rows in the span should return the same range.
+    /// [`Self::cell_ui`] is then called once for the whole span, see [`CellInfo::row_range`],
+    /// also when the top of the span has been scrolled out of view,
+    /// or is a pinned row left out of the body (see [`Table::show_pinned_rows_in_body`]).
+    /// If the cell also spans several columns, the [`Self::cell_span`] of the first row is used.
+    ///
+    /// Spans are ignored for the pinned rows and the sticky section header.
+    ///
+    /// The default implementation returns `row_nr..row_nr + 1`, i.e. no spanning.
+    fn cell_row_span(&self, row_nr: u64, _col_nr: usize) -> Range<u64> {
+        row_nr..row_nr + 1
+    }
+
     /// Compute the offset for the top of the given row.
     ///
     /// Implement this for arbitrary row heights. The default implementation uses
     /// [`Self::default_row_height`].
     ///
+    /// This is called many times per frame, so it should be fast.
+    /// [`RowHeights::top_offset`] is a good fit if only some rows have a height of their own.
+    ///
+    /// This is an `f64`, so that rows far down in huge tables can be told apart.
+    ///
+    /// Don't include the detail panels of the [`Table::detail_rows`],
+    /// they are added automatically.
+    ///
+    /// Not used if [`Table::auto_row_heights`] is turned on.
+    ///
     /// Note: must always return 0.0 for `row_nr = 0`.
-    fn row_top_offset(&self, _ctx: &Context, _table_id: Id, row_nr: u64) -> f32 {
-        row_nr as f32 * self.default_row_height()
+    fn row_top_offset_f64(&self, _ctx: &Context, _table_id: Id, row_nr: u64) -> f64 {
+        row_nr as f64 * f64::from(self.default_row_height())
+    }
+
+    /// [`Self::row_top_offset_f64`] as an `f32`.
+    ///
+    /// The table doesn't call this, so implement [`Self::row_top_offset_f64`] instead.
+    fn row_top_offset(&self, ctx: &Context, table_id: Id, row_nr: u64) -> f32 {
+        to_f32(self.row_top_offset_f64(ctx, table_id, row_nr))
     }
 
     /// Default row height.
     ///
-    /// This is used by the default implementation of [`Self::row_top_offset`].
+    /// This is used by the default implementation of [`Self::row_top_offset_f64`].
     fn default_row_height(&self) -> f32 {
         20.0
     }
-}
 
-impl Table {
-    /// Create a new table, with no columns and no headers, and zero rows.
-    #[inline]
-    pub fn new() -> Self {
-        Self::default()
+    /// The height of the detail panel below one of the [`Table::detail_rows`].
+    ///
+    /// The default implementation is four times [`Self::default_row_height`].
+    fn row_detail_height(&self, _ctx: &Context, _table_id: Id, _row_nr: u64) -> f32 {
+        4.0 * self.default_row_height()
     }
 
-    /// Salt added to the parent [`Ui::id`] to produce an [`Id`] that is unique
-    /// within the parent [`Ui`].
+    /// The contents of the detail panel below one of the [`Table::detail_rows`].
     ///
-    /// You need to set this to something unique if you have multiple tables in the same ui.
-    #[inline]
-    pub fn id_salt(mut self, id_salt: impl std::hash::Hash) -> Self {
-        self.id_salt = Id::new(id_salt);
-        self
-    }
+    /// The panel spans the full visible width of the table, including the sticky columns,
+    /// and ignores the column boundaries.
+    fn row_detail_ui(&mut self, _ui: &mut Ui, _row_nr: u64) {}
 
-    /// Total number of rows (sticky + non-sticky).
-    #[inline]
-    pub fn num_rows(mut self, num_rows: u64) -> Self {
-        self.num_rows = num_rows;
-        self
+    /// The section header row of the section that the given row is in, if any.
+    ///
+    /// Return `Some(row_nr)` for the section header rows themselves.
+    ///
+    /// While a section is scrolled through, its header row sticks to the top of the body,
+    /// until it is pushed out by the header of the next section.
+    /// The stuck header is shown with [`CellInfo::is_sticky_section_header`] set.
+    ///
+    /// The default implementation returns `None`, i.e. there are no sections.
+    fn section_header_row(&self, _row_nr: u64) -> Option<u64> {
+        None
     }
 
-    /// The columns of the table.
-    #[inline]
-    pub fn columns(mut self, columns: impl Into<Vec<Column>>) -> Self {
-        self.columns = columns.into();
-        self
+    /// A stable identity of the given row of your data source.
+    ///
+    /// The per-row state is keyed by this, so that it stays with its row when the data changes,
+    /// e.g. when rows are sorted, filtered, inserted or removed.
+    /// This is the [`TableState::selection`], [`TableState::focus`], [`TableState::pinned_rows`],
+    /// [`TableState::expanded_rows`], and the [`Ui`] ids of the rows and cells.
+    /// Selections of more than ten thousand rows only follow rows that were added or removed above them.
+    /// In tables of more than a hundred thousand rows, rows that moved far away are only found
+    /// with [`Self::source_row_of_id`].
+    ///
+    /// It is also used for keeping the row at the top of the view in place
+    /// when rows come and go above it, e.g. for a log that shows the newest entry first,
+    /// or when loading more rows at the top.
+    /// When scrolled all the way to the top, the view stays there, showing the new rows.
+    ///
+    /// Rows are only looked for when [`Table::num_rows`] or [`Table::row_mapping`] changes,
+    /// or after [`TableState::refresh_row_ids`].
+    ///
+    /// The default implementation uses the source row number,
+    /// which is enough if your rows only move around with a [`Table::row_mapping`].
+    fn row_id(&self, source_row: u64) -> Id {
+        Id::new(source_row)
     }
 
-    /// How many columns are sticky (non-scrolling)?
+    /// The source row with the given [`Self::row_id`], if you can look it up quickly.
     ///
-    /// Default is 0.
-    #[inline]
-    pub fn num_sticky_cols(mut self, num_sticky_cols: usize) -> Self {
-        self.num_sticky_cols = num_sticky_cols;
-        self
+    /// This is how the selection, focus and pinned rows find their rows again when rows move far,
+    /// e.g. when sorting a large table.
+    /// Without it, only the thousand rows around where a row was are searched for it
+    /// (or all rows, in tables of up to a hundred thousand rows).
+    ///
+    /// The default implementation returns `None`.
+    fn source_row_of_id(&self, _row_id: Id) -> Option<u64> {
+        None
     }
 
-    /// The count and parameters of the sticky (non-scrolling) header rows.
-    #[inline]
-    pub fn headers(mut self, headers: impl Into<Vec<HeaderRow>>) -> Self {
-        self.headers = headers.into();
-        self
+    /// The contents of a cell as text, for copying it to the clipboard.
+    ///
+    /// This takes the row of your data source, see [`CellInfo::source_row`].
+    /// The default implementation returns `None`, and if all cells are `None`, nothing is copied.
+    /// See [`Table::copy_format`] and [`Table::export`].
+    fn cell_text(&self, _source_row: u64, _col_nr: usize) -> Option<String> {
+        None
     }
 
-    /// How to do auto-sizing of columns, if at all.
-    #[inline]
-    pub fn auto_size_mode(mut self, auto_size_mode: AutoSizeMode) -> Self {
-        self.auto_size_mode = auto_size_mode;
-        self
-    }
+    /// The user pasted values into the table with Ctrl+V (Cmd+V on Mac).
+    ///
+    /// `grid` is the rows of pasted values, to put in the cells from `start_cell` and down to the right.
+    /// `start_cell` is the top left of the selected cells, or else the focused cell.
+    /// It uses visual rows and display positions, so the values go into the columns after it in
+    /// [`PrefetchInfo::column_order`] (or [`TableState::column_at`]).
+    /// The grid may reach beyond the last row or column, and rows can have different lengths.
+    ///
+    /// See [`Table::copy_format`] for how the text is split into values.
+    /// The default implementation does nothing.
+    fn paste(&mut self, _start_cell: CellCoord, _grid: Vec<Vec<String>>) {}
 
-    /// The scroll handle will stick to the bottom position even while the content size
-    /// changes dynamically.
+    /// The editor of a cell in an [`Column::editable`] column, shown instead of [`Self::cell_ui`] while the user edits it.
+    ///
+    /// `value` starts out as the [`Self::cell_text`] of the cell, or as what the user typed to start editing.
+    /// Return the [`Response`] of the widget that should have the keyboard focus.
+    ///
+    /// Enter commits the edit with [`Self::commit_edit`], and so does clicking elsewhere.
+    /// Tab (or Shift+Tab) commits it and moves on to the next (or previous) cell.
+    /// Escape cancels the edit.
+    ///
+    /// The default implementation shows a single-line [`egui::TextEdit`].
+    fn cell_editor_ui(&mut self, ui: &mut Ui, _cell: &CellInfo, value: &mut String) -> Response {
+        ui.add(egui::TextEdit::singleline(value).desired_width(ui.available_width()))
+    }
+
+    /// The user is done editing a cell, see [`Self::cell_editor_ui`].
+    ///
+    /// The default implementation accepts any value, without storing it anywhere.
+    ///
+    /// # Errors
+    /// Return a message saying what is wrong with the value to reject it.
+    /// The message is shown next to the editor, and the user keeps editing.
+    /// If the user clicked away, the editor stays open with the message, but without the keyboard focus.
+    fn commit_edit(&mut self, _cell: &CellInfo, _value: String) -> Result<(), String> {
+        Ok(())
+    }
+
+    /// The contents of a header cell as text, for copying it to the clipboard.
+    ///
+    /// Header rows where this returns `None` for all cells are left out.
+    /// The default implementation returns `None`.
+    fn header_cell_text(&self, _cell: &HeaderCellInfo) -> Option<String> {
+        None
+    }
+
+    /// The name of a column, as shown in the column chooser.
+    ///
+    /// See [`Table::column_chooser`].
+    fn column_name(&self, col_nr: usize) -> String {
+        format!("Column {col_nr}")
+    }
+
+    /// Called when the user clicks the header of a [`Column::sortable`] column.
+    ///
+    /// The table does not sort anything itself, so this is where you reorder your data.
+    /// The new sort is also stored in [`TableState::sort`].
+    fn on_sort_changed(&mut self, _sort: &SortState) {}
+
+    /// Which filter widget to show for a column in the [`Table::filter_row`].
+    ///
+    /// The default implementation returns `None`, i.e. no filter.
+    fn column_filter(&self, _col_nr: usize) -> Option<ColumnFilter> {
+        None
+    }
+
+    /// Show the filter widget of a column in the [`Table::filter_row`].
+    ///
+    /// `value` is what the user has entered so far, or [`FilterValue::empty`].
+    /// Return `true` if the user changed it.
+    ///
+    /// The default implementation calls [`crate::filter::filter_ui`].
+    fn filter_ui(
+        &mut self,
+        ui: &mut Ui,
+        _col_nr: usize,
+        filter: &ColumnFilter,
+        value: &mut FilterValue,
+    ) -> bool {
+        crate::filter::filter_ui(ui, filter, value)
+    }
+
+    /// Called when the user changes a filter in the [`Table::filter_row`].
+    ///
+    /// The table does not filter anything itself, so this is where you update your
+    /// [`Table::row_mapping`], e.g. with [`FilterState::matches`].
+    /// The new filters are also stored in [`TableState::filters`].
+    fn on_filters_changed(&mut self, _filters: &FilterState) {}
+
+    /// Paint the sort indicator in the header cell of a sorted column.
+    ///
+    /// This is called after [`Self::header_cell_ui`].
+    /// `priority` is the index of the column in [`SortState::keys`].
+    ///
+    /// The default implementation calls [`crate::sort::paint_sort_indicator`].
+    fn sort_indicator_ui(
+        &mut self,
+        ui: &mut Ui,
+        direction: SortDirection,
+        priority: usize,
+        num_sort_keys: usize,
+    ) {
+        crate::sort::paint_sort_indicator(ui, direction, priority, num_sort_keys);
+    }
+
+    /// Where the given row of your data source is in the tree.
+    ///
+    /// Only used if you have set a [`Table::tree_column`].
+    /// The default implementation puts all rows at the root, without children.
+    fn tree_node(&self, _source_row: u64) -> TreeNode {
+        TreeNode::default()
+    }
+
+    /// Indent a cell in the [`Table::tree_column`] and show its triangle for expanding and collapsing the row.
+    ///
+    /// This is called before [`Self::cell_ui`], with the same [`Ui`].
+    /// Return `true` if the user clicked to expand or collapse the row.
+    ///
+    /// The default implementation calls [`crate::tree::disclosure_ui`].
+    fn tree_disclosure_ui(&mut self, ui: &mut Ui, node: TreeNode, is_expanded: bool) -> bool {
+        crate::tree::disclosure_ui(ui, node, is_expanded)
+    }
+}
+
+impl Table {
+    /// Create a new table, with no columns and no headers, and zero rows.
+    #[inline]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Salt added to the parent [`Ui::id`] to produce an [`Id`] that is unique
+    /// within the parent [`Ui`].
+    ///
+    /// You need to set this to something unique if you have multiple tables in the same ui.
+    #[inline]
+    pub fn id_salt(mut self, id_salt: impl std::hash::Hash) -> Self {
+        self.id_salt = Id::new(id_salt);
+        self
+    }
+
+    /// Total number of rows (sticky + non-sticky).
+    #[inline]
+    pub fn num_rows(mut self, num_rows: u64) -> Self {
+        self.num_rows = num_rows;
+        self
+    }
+
+    /// Show a sorted and/or filtered view of your data.
+    ///
+    /// Each visual row `row_nr` shows the source row `mapping.source_row(row_nr)`,
+    /// given to you as [`CellInfo::source_row`].
+    /// Everything else, like [`TableDelegate::row_top_offset_f64`] and the selection, uses visual rows.
+    ///
+    /// This overrides [`Self::num_rows`] with [`RowMapping::len`].
+    /// The mapping is shared, so you can keep it around between frames without copying it.
+    ///
+    /// Can't be combined with [`Self::tree_column`].
+    #[inline]
+    pub fn row_mapping(mut self, row_mapping: impl Into<Arc<RowMapping>>) -> Self {
+        self.row_mapping = Some(row_mapping.into());
+        self
+    }
+
+    /// Show the rows as a tree, with expandable parent rows.
+    ///
+    /// Each source row describes where it is in the tree with [`TableDelegate::tree_node`],
+    /// and [`Self::num_rows`] is the total number of rows in the tree.
+    /// The column with this index is indented according to the depth of each row,
+    /// and shows a triangle for expanding and collapsing it (see [`TableDelegate::tree_disclosure_ui`]).
+    ///
+    /// Which rows are expanded is stored in [`TableState::expanded_rows`].
+    /// Only the rows with all their ancestors expanded are shown, as the visual rows,
+    /// with [`CellInfo::source_row`] telling you which row of the tree is shown.
+    /// These are cached, see [`TableState::refresh_tree`].
+    ///
+    /// The tree can't be sorted or filtered, since the rows must stay in depth-first order,
+    /// so this can't be combined with [`Self::row_mapping`].
+    /// Sort and filter the rows of your tree yourself instead.
+    #[inline]
+    pub fn tree_column(mut self, col_nr: usize) -> Self {
+        self.tree_column = Some(col_nr);
+        self
+    }
+
+    /// Measure the height of each row from its contents, instead of asking [`TableDelegate::row_top_offset_f64`].
+    ///
+    /// Rows that haven't been shown yet are assumed to be [`TableDelegate::default_row_height`] high,
+    /// and no row is shorter than that.
+    /// As rows are shown and measured, the scroll position is corrected
+    /// so that the rows in view don't jump around.
+    ///
+    /// The measured heights are cached in [`TableState::row_heights`].
+    ///
+    /// Default is `false`.
+    #[inline]
+    pub fn auto_row_heights(mut self, auto_row_heights: bool) -> Self {
+        self.auto_row_heights = auto_row_heights;
+        self
+    }
+
+    /// These rows have a detail panel below them, spanning the full width of the table.
+    ///
+    /// The panel is shown with [`TableDelegate::row_detail_ui`],
+    /// and is [`TableDelegate::row_detail_height`] high.
+    /// The heights are added to [`TableDelegate::row_top_offset_f64`] automatically.
+    ///
+    /// These are visual rows.
+    #[inline]
+    pub fn detail_rows(mut self, rows: impl IntoIterator<Item = u64>) -> Self {
+        self.detail_rows = rows.into_iter().collect();
+        self
+    }
+
+    /// The columns of the table.
+    #[inline]
+    pub fn columns(mut self, columns: impl Into<Vec<Column>>) -> Self {
+        self.columns = columns.into();
+        self
+    }
+
+    /// How many columns are sticky (non-scrolling)?
+    ///
+    /// Default is 0.
+    #[inline]
+    pub fn num_sticky_cols(mut self, num_sticky_cols: usize) -> Self {
+        self.num_sticky_cols = num_sticky_cols;
+        self
+    }
+
+    /// How many of the last columns are sticky (non-scrolling), pinned to the right edge of the table?
+    ///
+    /// Default is 0.
+    #[inline]
+    pub fn num_sticky_cols_right(mut self, num_sticky_cols_right: usize) -> Self {
+        self.num_sticky_cols_right = num_sticky_cols_right;
+        self
+    }
+
+    /// The count and parameters of the sticky (non-scrolling) header rows.
+    #[inline]
+    pub fn headers(mut self, headers: impl Into<Vec<HeaderRow>>) -> Self {
+        self.headers = headers.into();
+        self
+    }
+
+    /// The count and parameters of the footer rows, stuck to the bottom of the table.
+    ///
+    /// These are shown with [`TableDelegate::footer_cell_ui`].
+    ///
+    /// Default is no footer.
+    #[inline]
+    pub fn footers(mut self, footers: impl Into<Vec<FooterRow>>) -> Self {
+        self.footers = footers.into();
+        self
+    }
+
+    /// Should the [`TableState::pinned_rows`] also be shown at their usual place in the body?
+    ///
+    /// If `false`, they are only shown pinned below the header.
+    ///
+    /// Default is `true`.
+    #[inline]
+    pub fn show_pinned_rows_in_body(mut self, show_pinned_rows_in_body: bool) -> Self {
+        self.show_pinned_rows_in_body = show_pinned_rows_in_body;
+        self
+    }
+
+    /// How to do auto-sizing of columns, if at all.
+    #[inline]
+    pub fn auto_size_mode(mut self, auto_size_mode: AutoSizeMode) -> Self {
+        self.auto_size_mode = auto_size_mode;
+        self
+    }
+
+    /// The scroll handle will stick to the bottom position even while the content size
+    /// changes dynamically.
     ///
     /// This can be useful to simulate terminal UIs or log/info scrollers.
     /// The scroll handle remains stuck until user manually changes position. Once "unstuck"
@@ -295,6 +1147,78 @@ impl Table {
         self
     }
 
+    /// How the user can select rows.
+    ///
+    /// The selection is stored in [`TableState::selection`],
+    /// and the selected rows are highlighted before [`TableDelegate::row_ui`] is called.
+    ///
+    /// Default is [`SelectionMode::None`].
+    #[inline]
+    pub fn selection_mode(mut self, selection_mode: SelectionMode) -> Self {
+        self.selection_mode = selection_mode;
+        self
+    }
+
+    /// Let the user move the focused cell with the arrow keys, Page Up/Down, Home and End,
+    /// once the table has keyboard focus.
+    ///
+    /// The table takes keyboard focus when the user clicks a row,
+    /// and the clicked cell becomes the [`TableState::focus`].
+    /// Holding Shift extends the selection, see [`Self::selection_mode`].
+    ///
+    /// Default is `false`.
+    #[inline]
+    pub fn keyboard_navigation(mut self, keyboard_navigation: bool) -> Self {
+        self.keyboard_navigation = keyboard_navigation;
+        self
+    }
+
+    /// Let the user hide and show columns by right-clicking a header cell.
+    ///
+    /// The columns are listed using [`TableDelegate::column_name`],
+    /// and which are shown is stored in [`TableState::col_visible`].
+    ///
+    /// Default is `false`.
+    #[inline]
+    pub fn column_chooser(mut self, column_chooser: bool) -> Self {
+        self.column_chooser = column_chooser;
+        self
+    }
+
+    /// How to write the cells when the user presses Ctrl+C (Cmd+C on Mac).
+    ///
+    /// The selected cells or rows are copied, or the rows in view if nothing is selected.
+    /// Shown columns are copied in the order they are shown,
+    /// with header rows from [`TableDelegate::header_cell_text`].
+    /// Nothing is copied unless you implement [`TableDelegate::cell_text`].
+    ///
+    /// Only the first hundred thousand rows are copied, since the text is built in a single frame.
+    /// Use [`Self::export`] to write out more rows than that.
+    ///
+    /// Pasting with Ctrl+V calls [`TableDelegate::paste`].
+    /// Text with tabs in it is pasted as TSV, which is what spreadsheets copy,
+    /// and other text as CSV if this is [`TextFormat::Csv`].
+    ///
+    /// Default is [`TextFormat::Tsv`], which can be pasted into spreadsheets.
+    #[inline]
+    pub fn copy_format(mut self, copy_format: TextFormat) -> Self {
+        self.copy_format = copy_format;
+        self
+    }
+
+    /// Show a row of filter widgets below the header, this high.
+    ///
+    /// Each column gets the widget of its [`TableDelegate::column_filter`], if any.
+    /// What the user enters is stored in [`TableState::filters`],
+    /// and you are told about changes with [`TableDelegate::on_filters_changed`].
+    ///
+    /// Default is no filter row.
+    #[inline]
+    pub fn filter_row(mut self, height: f32) -> Self {
+        self.filter_row = Some(height);
+        self
+    }
+
     /// Read the globally unique id, based on the current [`Self::id_salt`]
     /// and the parent id.
     #[inline]
@@ -348,10 +1272,9 @@ impl Table {
         self
     }
 
-    /// The top y coordinate offset of a specific row nr.
+    /// The top y coordinate offset of a specific row nr, in the scroll area of the body.
     ///
-    /// `get_row_top_offset(0)` should always return 0.0.
-    #[expect(clippy::unused_self)] // for uniformity
+    /// `get_row_top_offset(0)` is 0.0, unless the table is taller than [`MAX_SCROLL_HEIGHT`].
     fn get_row_top_offset(
         &self,
         ctx: &Context,
@@ -359,580 +1282,4360 @@ impl Table {
         table_delegate: &dyn TableDelegate,
         row_nr: u64,
     ) -> f32 {
-        table_delegate.row_top_offset(ctx, table_id, row_nr)
+        to_f32(self.body_row_top_offset(ctx, table_id, table_delegate, row_nr) - self.scroll_origin)
     }
 
-    /// Which row contains the given y offset (from the top)?
-    fn get_row_nr_at_y_offset(
+    /// The top y coordinate offset of a specific row nr, from the top of the body.
+    fn body_row_top_offset(
         &self,
         ctx: &Context,
         table_id: Id,
         table_delegate: &dyn TableDelegate,
-        y_offset: f32,
-    ) -> u64 {
-        partition_point(0..=self.num_rows, |row_nr| {
-            y_offset <= self.get_row_top_offset(ctx, table_id, table_delegate, row_nr)
-        })
-        .saturating_sub(1)
+        row_nr: u64,
+    ) -> f64 {
+        let offset = self.base_row_top_offset(ctx, table_id, table_delegate, row_nr);
+
+        // The detail panels of the rows above:
+        let num_details_above = self.details.partition_point(|(row, _, _)| *row < row_nr);
+        let offset = offset
+            + self.details[..num_details_above]
+                .last()
+                .map_or(0.0, |(_, height, heights_above)| {
+                    f64::from(*height) + heights_above
+                });
+
+        if self.show_pinned_rows_in_body {
+            offset
+        } else {
+            // The pinned rows take up no space in the body:
+            offset
+                - self
+                    .pinned_rows
+                    .iter()
+                    .filter(|(pinned, _)| *pinned < row_nr)
+                    .map(|(pinned, height)| f64::from(height + self.detail_height(*pinned)))
+                    .sum::<f64>()
+        }
     }
 
-    pub fn show(mut self, ui: &mut Ui, table_delegate: &mut dyn TableDelegate) -> Response {
-        self.num_sticky_cols = self.num_sticky_cols.at_most(self.columns.len());
-
-        let id = TableState::id(ui, self.id_salt);
-        let state = TableState::load(ui.ctx(), id);
-        let is_new = state.is_none();
-        let do_full_sizing_pass = is_new;
-        let mut state = state.unwrap_or_default();
+    /// The top of the given row, not counting the detail panels or pinned rows.
+    fn base_row_top_offset(
+        &self,
+        ctx: &Context,
+        table_id: Id,
+        table_delegate: &dyn TableDelegate,
+        row_nr: u64,
+    ) -> f64 {
+        if self.auto_row_heights {
+            self.row_heights.top_offset(row_nr)
+        } else {
+            table_delegate.row_top_offset_f64(ctx, table_id, row_nr)
+        }
+    }
 
-        for (i, column) in self.columns.iter_mut().enumerate() {
-            let column_id = column.id_for(i);
-            if let Some(existing_width) = state.col_widths.get(&column_id) {
-                column.current = *existing_width;
+    /// Remember the row heights measured this frame.
+    ///
+    /// If the rows above the ones in view changed height, the view is scrolled to keep them in place.
+    fn update_row_heights(
+        &mut self,
+        ctx: &Context,
+        id: Id,
+        state: &mut TableState,
+        table_delegate: &dyn TableDelegate,
+        measured: BTreeMap<u64, f32>,
+    ) {
+        if self.num_rows == 0 {
+            // Nothing was measured, but keep the heights for when the rows come back:
+            state.row_heights = std::mem::take(&mut self.row_heights);
+            return;
+        }
+        let estimate = table_delegate.default_row_height();
+        let top_row = self.get_row_nr_at_y_offset(ctx, id, table_delegate, state.scroll_offset.y);
+
+        let mut heights = std::mem::take(&mut self.row_heights);
+        let mut changed = false;
+        for (row_nr, height) in measured {
+            let height = height.at_least(estimate);
+            let old_height = heights.height(row_nr);
+            if 0.5 < (height - old_height).abs() {
+                changed = true;
+                if row_nr < top_row {
+                    state.scroll_correction += height - old_height;
+                }
+                heights.set_height(row_nr, height);
             }
-            column.current = column.range.clamp(column.current);
+        }
+        state.row_heights = heights;
 
-            if do_full_sizing_pass {
-                column.auto_size_this_frame = true;
-            }
+        if changed {
+            // Redo the layout with the new heights:
+            ctx.request_discard("egui_table row heights changed");
         }
+    }
 
-        let parent_width = ui.available_width();
-        let auto_size = match self.auto_size_mode {
-            AutoSizeMode::Never => false,
-            AutoSizeMode::Always => true,
-            AutoSizeMode::OnParentResize => state.parent_width != Some(parent_width),
-        };
-        if auto_size {
-            Column::auto_size(&mut self.columns, parent_width);
+    /// The height of the detail panel below the given row, if it has one.
+    fn detail_height(&self, row_nr: u64) -> f32 {
+        self.details
+            .binary_search_by_key(&row_nr, |(row, _, _)| *row)
+            .map_or(0.0, |index| self.details[index].1)
+    }
+
+    /// Is this row left out of the body?
+    fn is_hidden_in_body(&self, row_nr: u64) -> bool {
+        !self.show_pinned_rows_in_body
+            && self.pinned_rows.iter().any(|(pinned, _)| *pinned == row_nr)
+    }
+
+    /// The source row shown at the given visual row, see [`Self::row_mapping`].
+    fn source_row(&self, row_nr: u64) -> u64 {
+        self.row_mapping
+            .as_ref()
+            .and_then(|row_mapping| row_mapping.source_row(row_nr))
+            .unwrap_or(row_nr)
+    }
+
+    /// The [`TableDelegate::row_id`] of the given visual row.
+    fn row_id(&self, table_delegate: &dyn TableDelegate, row_nr: u64) -> Id {
+        table_delegate.row_id(self.source_row(row_nr))
+    }
+
+    /// Remember the [`TableDelegate::row_id`] of the rows that the [`TableState`] refers to.
+    ///
+    /// Only the rows that weren't tracked before are looked up, unless the rows have changed.
+    fn remember_row_ids(&self, state: &mut TableState, table_delegate: &dyn TableDelegate) {
+        let old = std::mem::take(&mut state.tracked_rows);
+        let old_ids = old
+            .is_up_to_date(self, state.row_ids_generation)
+            .then_some(old.ids);
+
+        let selection = &state.selection;
+        let track_selected_rows = selection.rows.len() <= MAX_TRACKED_SELECTED_ROWS;
+
+        let mut rows = state.pinned_rows.clone();
+        rows.extend(state.focus.map(|focus| focus.row_nr));
+        rows.extend(state.editing.as_ref().map(|edit| edit.cell.row_nr));
+        rows.extend(selection.anchor);
+        // So that we know how the rows moved, for the measured row heights:
+        rows.extend(state.scroll_anchor.map(|anchor| anchor.row_nr));
+        if let Some(cells) = selection.cells {
+            rows.extend([cells.anchor.row_nr, cells.focus.row_nr]);
+        }
+        if track_selected_rows {
+            rows.extend(selection.rows.iter());
         }
-        state.parent_width = Some(parent_width);
 
-        let col_x = {
-            let mut x = ui.cursor().min.x;
-            let mut col_x = Vec1::with_capacity(x, self.columns.len() + 1);
-            for column in &self.columns {
-                x += column.current;
-                col_x.push(x);
-            }
-            col_x
+        state.tracked_rows = TrackedRows {
+            num_rows: self.num_rows,
+            row_mapping: self.row_mapping.as_ref().map(Arc::downgrade),
+            generation: state.row_ids_generation,
+            ids: rows
+                .into_iter()
+                .filter(|&row_nr| row_nr < self.num_rows)
+                .map(|row_nr| {
+                    let old_id = old_ids.as_ref().and_then(|ids| ids.get(&row_nr));
+                    let row_id =
+                        old_id.map_or_else(|| self.row_id(table_delegate, row_nr), |id| *id);
+                    (row_nr, row_id)
+                })
+                .collect(),
+            has_selected_rows: track_selected_rows,
         };
+    }
 
-        let header_row_y = {
-            let mut y = ui.cursor().min.y;
-            let mut sticky_row_y = Vec1::with_capacity(y, self.headers.len() + 1);
-            for header in &self.headers {
-                y += header.height;
-                sticky_row_y.push(y);
+    /// Move the selection, focus and pinned rows to where their rows are now,
+    /// if they moved since last frame.
+    fn follow_row_ids(
+        &self,
+        state: &mut TableState,
+        table_delegate: &dyn TableDelegate,
+    ) -> MovedRows {
+        if state
+            .tracked_rows
+            .is_up_to_date(self, state.row_ids_generation)
+        {
+            return MovedRows::None; // The rows haven't changed
+        }
+        let tracked = std::mem::take(&mut state.tracked_rows);
+        let is_at = |row_nr: u64, row_id: Id| {
+            row_nr < self.num_rows && self.row_id(table_delegate, row_nr) == row_id
+        };
+        if tracked
+            .ids
+            .iter()
+            .all(|(&row_nr, &row_id)| is_at(row_nr, row_id))
+        {
+            return MovedRows::None;
+        }
+
+        let new_row_nrs = self.find_moved_rows(&tracked, table_delegate);
+        let is_shifted = new_row_nrs.len() == tracked.ids.len()
+            && new_row_nrs.iter().all(|(&row_nr, &new_row_nr)| {
+                new_row_nr == shifted_row_nr(row_nr, tracked.num_rows, self.num_rows)
+            });
+        let moved = if is_shifted {
+            MovedRows::Shifted {
+                old_num_rows: tracked.num_rows,
             }
-            sticky_row_y
+        } else {
+            MovedRows::Reordered
+        };
+        let new_row_nr = |row_nr: u64| new_row_nrs.get(&row_nr).copied();
+        let new_cell = |cell: CellCoord| {
+            Some(CellCoord {
+                row_nr: new_row_nr(cell.row_nr)?,
+                ..cell
+            })
         };
 
-        let sticky_size = Vec2::new(
-            self.columns[..self.num_sticky_cols]
-                .iter()
-                .map(|c| c.current)
-                .sum(),
-            self.headers.iter().map(|h| h.height).sum(),
-        );
+        state.pinned_rows = state
+            .pinned_rows
+            .iter()
+            .filter_map(|&row_nr| new_row_nr(row_nr))
+            .collect();
+        state.focus = state.focus.and_then(new_cell);
+        state.editing = state.editing.take().and_then(|edit| {
+            Some(CellEdit {
+                cell: new_cell(edit.cell)?,
+                ..edit
+            })
+        });
+
+        let selection = &mut state.selection;
+        selection.anchor = selection.anchor.and_then(new_row_nr);
+        selection.cells = selection.cells.and_then(|cells| {
+            Some(CellSelection {
+                anchor: new_cell(cells.anchor)?,
+                focus: new_cell(cells.focus)?,
+            })
+        });
+        if tracked.has_selected_rows {
+            let mut rows = RowSet::default();
+            for row_nr in selection.rows.iter().filter_map(new_row_nr) {
+                rows.insert(row_nr);
+            }
+            selection.rows = rows;
+        }
+
+        moved
+    }
+
+    /// Move the measured [`Self::row_heights`] along with their rows.
+    fn follow_moved_row_heights(&mut self, moved: MovedRows) {
+        match moved {
+            MovedRows::None => {}
+            MovedRows::Shifted { old_num_rows } => {
+                let mut row_heights = RowHeights::new(self.row_heights.default_height());
+                for (row_nr, height) in self.row_heights.iter() {
+                    let is_removed =
+                        self.num_rows < old_num_rows && row_nr < old_num_rows - self.num_rows;
+                    if !is_removed {
+                        let new_row_nr = shifted_row_nr(row_nr, old_num_rows, self.num_rows);
+                        row_heights.set_height(new_row_nr, height);
+                    }
+                }
+                self.row_heights = row_heights;
+            }
+            MovedRows::Reordered => {
+                // They are measured again as the rows come into view:
+                self.row_heights.clear();
+            }
+        }
+    }
+
+    /// Where are the tracked rows now?
+    ///
+    /// Rows that can't be found are left out.
+    fn find_moved_rows(
+        &self,
+        tracked: &TrackedRows,
+        table_delegate: &dyn TableDelegate,
+    ) -> BTreeMap<u64, u64> {
+        let is_at = |row_nr: u64, row_id: Id| {
+            row_nr < self.num_rows && self.row_id(table_delegate, row_nr) == row_id
+        };
+
+        let mut new_row_nrs = BTreeMap::new();
+        let mut lost_source_rows: BTreeMap<u64, u64> = BTreeMap::new(); // source row -> old row
+        let mut lost_ids: HashMap<Id, u64> = HashMap::new(); // row id -> old row
+        for (&row_nr, &row_id) in &tracked.ids {
+            // Most often, rows are added or removed above, moving all rows by the same amount:
+            let shifted = shifted_row_nr(row_nr, tracked.num_rows, self.num_rows);
+            if let Some(new_row_nr) = [row_nr, shifted]
+                .into_iter()
+                .find(|&new_row_nr| is_at(new_row_nr, row_id))
+            {
+                new_row_nrs.insert(row_nr, new_row_nr);
+            } else if let Some(source_row) = table_delegate
+                .source_row_of_id(row_id)
+                .filter(|&source_row| table_delegate.row_id(source_row) == row_id)
+            {
+                lost_source_rows.insert(source_row, row_nr);
+            } else {
+                lost_ids.insert(row_id, row_nr);
+            }
+        }
+
+        if !lost_source_rows.is_empty() {
+            if let Some(row_mapping) = &self.row_mapping {
+                for (new_row_nr, source_row) in row_mapping.as_slice().iter().enumerate() {
+                    if let Some(row_nr) = lost_source_rows.remove(source_row) {
+                        new_row_nrs.insert(row_nr, new_row_nr as u64);
+                    }
+                }
+            } else {
+                for (source_row, row_nr) in lost_source_rows {
+                    if source_row < self.num_rows {
+                        new_row_nrs.insert(row_nr, source_row);
+                    }
+                }
+            }
+        }
+
+        if !lost_ids.is_empty() {
+            // Only look at the rows around where the lost rows were, and only so many of them:
+            let mut ranges: Vec<Range<u64>> = Vec::new();
+            if self.num_rows <= MAX_ROWS_TO_SEARCH {
+                ranges.push(0..self.num_rows);
+            } else {
+                let mut old_row_nrs: Vec<u64> = lost_ids.values().copied().collect();
+                old_row_nrs.sort_unstable();
+                for row_nr in old_row_nrs {
+                    let range = row_nr.saturating_sub(MAX_ROW_SEARCH_DISTANCE)
+                        ..(row_nr + MAX_ROW_SEARCH_DISTANCE + 1).min(self.num_rows);
+                    match ranges.last_mut() {
+                        Some(last) if range.start <= last.end => last.end = last.end.max(range.end),
+                        _ => ranges.push(range),
+                    }
+                }
+            }
+
+            for new_row_nr in ranges
+                .into_iter()
+                .flatten()
+                .take(usize::try_from(MAX_ROWS_TO_SEARCH).unwrap_or(usize::MAX))
+            {
+                if let Some(row_nr) = lost_ids.remove(&self.row_id(table_delegate, new_row_nr)) {
+                    new_row_nrs.insert(row_nr, new_row_nr);
+                    if lost_ids.is_empty() {
+                        break;
+                    }
+                }
+            }
+        }
+
+        new_row_nrs
+    }
+
+    /// Which row contains the given y offset (from the top of the scroll area)?
+    ///
+    /// This is 0 for an empty table.
+    fn get_row_nr_at_y_offset(
+        &self,
+        ctx: &Context,
+        table_id: Id,
+        table_delegate: &dyn TableDelegate,
+        y_offset: f32,
+    ) -> u64 {
+        if self.num_rows == 0 {
+            return 0;
+        }
+        let y_offset = self.scroll_origin + f64::from(y_offset);
+        partition_point(0..=self.num_rows, |row_nr| {
+            y_offset <= self.body_row_top_offset(ctx, table_id, table_delegate, row_nr)
+        })
+        .saturating_sub(1)
+    }
+
+    /// Can the user click the body rows, to select them, focus a cell, or edit it?
+    ///
+    /// Otherwise the rows leave all clicks to the widgets in the cells.
+    fn has_clickable_rows(&self) -> bool {
+        self.selection_mode != SelectionMode::None
+            || self.keyboard_navigation
+            || self.columns.iter().any(|column| column.editable)
+    }
+
+    /// Move the window of the body that the scroll area covers, see [`MAX_SCROLL_HEIGHT`].
+    ///
+    /// The scroll offset is changed to match next time the table is shown,
+    /// so that the view stays put.
+    fn move_scroll_window(&mut self, state: &mut TableState, new_origin: f64) {
+        let shift = to_f32(self.scroll_origin - new_origin);
+        // Use exactly the shift we scroll by, so that we never drift:
+        self.scroll_origin -= f64::from(shift);
+        state.scroll_origin = self.scroll_origin;
+        state.scroll_correction += shift;
+    }
+
+    /// Move the scroll window to the given body offset, and scroll there.
+    ///
+    /// Used for scrolling to rows outside of the scroll window.
+    fn jump_scroll_window(&mut self, state: &mut TableState, body_height: f64, y_offset: f64) {
+        self.scroll_origin = Self::scroll_origin_around(body_height, y_offset);
+        state.scroll_origin = self.scroll_origin;
+        state.scroll_correction = to_f32(y_offset - self.scroll_origin) - state.scroll_offset.y;
+    }
+
+    /// Load the scroll window from the state, and move it if the rows changed since last frame.
+    fn update_scroll_window(
+        &mut self,
+        ctx: &Context,
+        id: Id,
+        state: &mut TableState,
+        table_delegate: &dyn TableDelegate,
+        body_height: f64,
+    ) {
+        self.scroll_origin = state.scroll_origin;
+        let max_scroll_origin = Self::scroll_origin_around(body_height, f64::INFINITY);
+        if max_scroll_origin < self.scroll_origin {
+            // The table got shorter:
+            self.move_scroll_window(state, max_scroll_origin);
+        }
+        self.follow_scroll_anchor(ctx, id, state, table_delegate, body_height);
+    }
+
+    /// Remember which row is at the top of the view, see [`TableDelegate::row_id`].
+    fn scroll_anchor(
+        &self,
+        ctx: &Context,
+        id: Id,
+        state: &TableState,
+        table_delegate: &dyn TableDelegate,
+    ) -> Option<ScrollAnchor> {
+        let view_top = self.scroll_origin + f64::from(state.scroll_offset.y);
+        if view_top <= 0.0 {
+            // Stay at the top, so that rows added above come into view:
+            return None;
+        }
+        if self.num_rows == 0 {
+            return None;
+        }
+        let row_nr = self.get_row_nr_at_y_offset(ctx, id, table_delegate, state.scroll_offset.y);
+        (row_nr < self.num_rows).then(|| ScrollAnchor {
+            row_id: self.row_id(table_delegate, row_nr),
+            row_nr,
+            offset_in_row: to_f32(
+                view_top - self.body_row_top_offset(ctx, id, table_delegate, row_nr),
+            ),
+            num_rows: self.num_rows,
+        })
+    }
+
+    /// If the row at the top of the view moved since last frame, scroll along with it.
+    fn follow_scroll_anchor(
+        &mut self,
+        ctx: &Context,
+        id: Id,
+        state: &mut TableState,
+        table_delegate: &dyn TableDelegate,
+        body_height: f64,
+    ) {
+        let Some(anchor) = state.scroll_anchor.take() else {
+            return;
+        };
+        if anchor.row_nr < self.num_rows
+            && self.row_id(table_delegate, anchor.row_nr) == anchor.row_id
+        {
+            return; // Nothing moved
+        }
+        let Some(row_nr) = self.find_anchor_row(table_delegate, &anchor) else {
+            return; // The row is gone
+        };
+
+        let view_top = self.body_row_top_offset(ctx, id, table_delegate, row_nr)
+            + f64::from(anchor.offset_in_row);
+        if view_top < self.scroll_origin || self.scroll_origin + MAX_SCROLL_HEIGHT <= view_top {
+            self.jump_scroll_window(state, body_height, view_top);
+        } else {
+            // This replaces any correction for changed row heights, since those were for the old row numbers:
+            state.scroll_correction = to_f32(view_top - self.scroll_origin) - state.scroll_offset.y;
+        }
+    }
+
+    /// Where the row of the anchor is now, if it is still near where we expect it.
+    fn find_anchor_row(
+        &self,
+        table_delegate: &dyn TableDelegate,
+        anchor: &ScrollAnchor,
+    ) -> Option<u64> {
+        // Most often, rows are added or removed above the view:
+        let guess = shifted_row_nr(anchor.row_nr, anchor.num_rows, self.num_rows);
+        let is_anchor = |row_nr: u64| {
+            row_nr < self.num_rows && self.row_id(table_delegate, row_nr) == anchor.row_id
+        };
+        (0..=MAX_SCROLL_ANCHOR_DISTANCE).find_map(|distance| {
+            [guess.checked_add(distance), guess.checked_sub(distance)]
+                .into_iter()
+                .flatten()
+                .find(|&row_nr| is_anchor(row_nr))
+        })
+    }
+
+    /// Move the scroll window if the view is getting close to its edge.
+    ///
+    /// Only call this when the user isn't scrolling,
+    /// so that we don't interfere with dragging the scroll bar or scroll animations.
+    fn follow_view_with_scroll_window(&mut self, state: &mut TableState, body_height: f64) {
+        let view_top = self.scroll_origin + f64::from(state.scroll_offset.y);
+        let new_origin = Self::scroll_origin_around(body_height, view_top);
+        if 0.25 * MAX_SCROLL_HEIGHT < (new_origin - self.scroll_origin).abs() {
+            self.move_scroll_window(state, new_origin);
+        }
+    }
+
+    /// The best [`TableState::scroll_origin`] for having the given body offset in the middle of the scroll area.
+    fn scroll_origin_around(body_height: f64, y_offset: f64) -> f64 {
+        let max_origin = (body_height - MAX_SCROLL_HEIGHT).at_least(0.0);
+        (y_offset - 0.5 * MAX_SCROLL_HEIGHT).clamp(0.0, max_origin)
+    }
+
+    /// Copy with Ctrl+C, and paste with Ctrl+V.
+    fn clipboard_ui(
+        &self,
+        ui: &Ui,
+        id: Id,
+        state: &TableState,
+        table_delegate: &mut dyn TableDelegate,
+        display_columns: &[usize],
+    ) {
+        let (copy, paste) = ui.input(|i| {
+            let copy = i.events.contains(&egui::Event::Copy);
+            let paste = i.events.iter().find_map(|event| match event {
+                egui::Event::Paste(text) => Some(text.clone()),
+                _ => None,
+            });
+            (copy, paste)
+        });
+
+        if copy && let Some(text) = self.selection_text(id, state, table_delegate, display_columns)
+        {
+            ui.ctx().copy_text(text);
+        }
+
+        // Paste into the top left of the selected cells, or else the focused cell:
+        let start_cell = state
+            .selected_cells()
+            .map(|cells| CellCoord {
+                row_nr: *cells.rows.start(),
+                col_pos: *cells.columns.start(),
+            })
+            .or(state.focus);
+        if let Some(text) = paste
+            && let Some(start_cell) = start_cell
+        {
+            // Spreadsheets copy tab separated values:
+            let format = if self.copy_format == TextFormat::Csv && !text.contains('\t') {
+                TextFormat::Csv
+            } else {
+                TextFormat::Tsv
+            };
+            let grid = crate::export::parse_separated(&text, format);
+            if !grid.is_empty() {
+                table_delegate.paste(start_cell, grid);
+                ui.ctx().request_discard("egui_table paste");
+            }
+        }
+    }
+
+    /// The text to copy to the clipboard, see [`Self::copy_format`].
+    ///
+    /// This is the selected cells or rows, or the rows in view if nothing is selected,
+    /// up to [`MAX_ROWS_TO_COPY`] of them.
+    fn selection_text(
+        &self,
+        id: Id,
+        state: &TableState,
+        table_delegate: &dyn TableDelegate,
+        display_columns: &[usize],
+    ) -> Option<String> {
+        let (rows, col_pos_range): (Box<dyn Iterator<Item = u64>>, _) =
+            if let Some(cells) = state.selected_cells() {
+                let end = (cells.columns.end() + 1).at_most(display_columns.len());
+                (Box::new(cells.rows), *cells.columns.start()..end)
+            } else if !state.selection.rows.is_empty() {
+                (
+                    Box::new(state.selection.rows.iter()),
+                    0..display_columns.len(),
+                )
+            } else {
+                (
+                    Box::new(state.visible_rows.clone()),
+                    0..display_columns.len(),
+                )
+            };
+        let col_nrs = display_columns.get(col_pos_range.clone())?;
+
+        let header_rows =
+            self.header_texts(id, state, table_delegate, display_columns, col_pos_range);
+        let mut writer =
+            TableWriter::new(vec![], self.copy_format, col_nrs.len(), &header_rows).ok()?;
+        let rows = rows
+            .take_while(|&row_nr| row_nr < self.num_rows)
+            .take(usize::try_from(MAX_ROWS_TO_COPY).unwrap_or(usize::MAX));
+        let has_text = self
+            .write_rows(&mut writer, rows, col_nrs, table_delegate)
+            .ok()?;
+        let text = String::from_utf8(writer.finish().ok()?).ok()?;
+        has_text.then_some(text)
+    }
+
+    /// Write the [`TableDelegate::cell_text`] of the given visual rows and columns.
+    ///
+    /// Returns `true` if any cell had a text.
+    fn write_rows<W: io::Write>(
+        &self,
+        writer: &mut TableWriter<W>,
+        rows: impl Iterator<Item = u64>,
+        col_nrs: &[usize],
+        table_delegate: &dyn TableDelegate,
+    ) -> io::Result<bool> {
+        let mut has_text = false;
+        for row_nr in rows {
+            let source_row = self.source_row(row_nr);
+            let cells = col_nrs.iter().map(|&col_nr| {
+                let text = table_delegate.cell_text(source_row, col_nr);
+                has_text |= text.is_some();
+                text.unwrap_or_default()
+            });
+            writer.write_row(cells)?;
+        }
+        Ok(has_text)
+    }
+
+    /// The header rows above the given display positions, see [`TableDelegate::header_cell_text`].
+    fn header_texts(
+        &self,
+        id: Id,
+        state: &TableState,
+        table_delegate: &dyn TableDelegate,
+        display_columns: &[usize],
+        col_pos_range: Range<usize>,
+    ) -> Vec<Vec<HeaderText>> {
+        let header_groups = self.header_groups(display_columns);
+        let num_header_rows = header_groups.len();
+        let mut header_rows = vec![];
+        for (row_nr, groups) in header_groups.into_iter().enumerate() {
+            let mut cells = vec![];
+            let mut col_pos = col_pos_range.start;
+            let mut has_text = false;
+            for group in groups {
+                let start = group.col_pos_range.start.max(col_pos);
+                let end = group.col_pos_range.end.min(col_pos_range.end);
+                if end <= start {
+                    continue;
+                }
+                if col_pos < start {
+                    // Columns without a header cell:
+                    cells.push(HeaderText::new("", start - col_pos));
+                }
+
+                // Only the header row closest to the body is sorted:
+                let col_nr = group.col_range.start;
+                let column = &self.columns[col_nr];
+                let sort_direction = (row_nr + 1 == num_header_rows
+                    && group.col_range.len() == 1
+                    && column.sortable)
+                    .then(|| state.sort.direction(column.id_for(col_nr)))
+                    .flatten();
+
+                let text = table_delegate.header_cell_text(&HeaderCellInfo {
+                    group_index: group.group_index,
+                    col_range: group.col_range,
+                    col_pos_range: group.col_pos_range,
+                    row_nr,
+                    sort_direction,
+                    table_id: id,
+                });
+                has_text |= text.is_some();
+                cells.push(HeaderText::new(text.unwrap_or_default(), end - start));
+                col_pos = end;
+            }
+            if col_pos < col_pos_range.end {
+                cells.push(HeaderText::new("", col_pos_range.end - col_pos));
+            }
+            if has_text {
+                header_rows.push(cells);
+            }
+        }
+        header_rows
+    }
+
+    /// The header cells of each header row, in display order.
+    fn header_groups(&self, display_columns: &[usize]) -> Vec<Vec<HeaderGroup>> {
+        self.headers
+            .iter()
+            .map(|header_row| group_columns(&header_row.groups, display_columns))
+            .collect()
+    }
+
+    /// The footer cells of each footer row, in display order.
+    fn footer_groups(&self, display_columns: &[usize]) -> Vec<Vec<HeaderGroup>> {
+        self.footers
+            .iter()
+            .map(|footer_row| group_columns(&footer_row.groups, display_columns))
+            .collect()
+    }
+
+    /// Move the focused cell with the arrow keys, Page Up/Down, Home and End.
+    ///
+    /// Returns `true` if the selection changed.
+    fn navigate_with_keys(
+        &mut self,
+        ui: &Ui,
+        id: Id,
+        state: &mut TableState,
+        table_delegate: &dyn TableDelegate,
+        num_columns: usize,
+        view_height: f32,
+    ) -> bool {
+        // Don't let egui move the keyboard focus to other widgets when we press the arrow keys:
+        ui.memory_mut(|mem| {
+            mem.set_focus_lock_filter(
+                id,
+                egui::EventFilter {
+                    horizontal_arrows: true,
+                    vertical_arrows: true,
+                    ..Default::default()
+                },
+            );
+        });
+
+        let key_presses = ui.input_mut(|i| {
+            let mut key_presses = vec![];
+            i.events.retain(|event| match event {
+                egui::Event::Key {
+                    key,
+                    pressed: true,
+                    modifiers,
+                    ..
+                } if NAVIGATION_KEYS.contains(key) => {
+                    key_presses.push((*key, *modifiers));
+                    false
+                }
+                _ => true,
+            });
+            key_presses
+        });
+
+        let old_selection = state.selection.clone();
+
+        for (key, modifiers) in key_presses {
+            let Some(focus) = state.focus else {
+                // Start at the top left corner:
+                if 0 < self.num_rows && 0 < num_columns {
+                    state.focus = Some(CellCoord {
+                        row_nr: 0,
+                        col_pos: 0,
+                    });
+                }
+                continue;
+            };
+
+            let focus_y = self.get_row_top_offset(ui.ctx(), id, table_delegate, focus.row_nr);
+            let page_size = self
+                .get_row_nr_at_y_offset(ui.ctx(), id, table_delegate, focus_y + view_height)
+                .saturating_sub(focus.row_nr)
+                .at_least(1);
+
+            let Some(new_focus) =
+                move_focus(focus, key, modifiers, self.num_rows, num_columns, page_size)
+            else {
+                continue;
+            };
+
+            state.focus = Some(new_focus);
+            state
+                .selection
+                .on_focus_move(self.selection_mode, new_focus, modifiers);
+
+            // Keep the focused cell visible:
+            self.scroll_to_cell(new_focus, num_columns);
+        }
+
+        state.selection != old_selection
+    }
+
+    /// Scroll just enough to bring the given cell into view.
+    fn scroll_to_cell(&mut self, cell: CellCoord, num_columns: usize) {
+        self.scroll_to_rows = Some((cell.row_nr..=cell.row_nr, None));
+        if self.num_sticky_cols <= cell.col_pos
+            && cell.col_pos < num_columns - self.num_sticky_cols_right
+        {
+            self.scroll_to_columns = Some((cell.col_pos..=cell.col_pos, None));
+        }
+    }
+
+    /// Start editing the given cell, if its column is [`Column::editable`].
+    ///
+    /// The editor starts out with `value`, or else the [`TableDelegate::cell_text`].
+    fn start_edit(
+        &self,
+        state: &mut TableState,
+        table_delegate: &dyn TableDelegate,
+        display_columns: &[usize],
+        cell: CellCoord,
+        placement: RowPlacement,
+        value: Option<String>,
+    ) {
+        let Some(&col_nr) = display_columns.get(cell.col_pos) else {
+            return;
+        };
+        if self.num_rows <= cell.row_nr || !self.columns[col_nr].editable {
+            return;
+        }
+        let value = value.unwrap_or_else(|| {
+            table_delegate
+                .cell_text(self.source_row(cell.row_nr), col_nr)
+                .unwrap_or_default()
+        });
+        // The editor would disappear with the section header when it no longer sticks:
+        let placement = if placement == RowPlacement::StickySection {
+            RowPlacement::Body
+        } else {
+            placement
+        };
+        state.editing = Some(CellEdit {
+            cell,
+            placement,
+            value,
+            error: None,
+            request_focus: true,
+            scroll_to: placement == RowPlacement::Body,
+        });
+    }
+
+    /// Start editing the focused cell with F2, or by typing into it.
+    fn start_edit_with_keys(
+        &self,
+        ui: &Ui,
+        state: &mut TableState,
+        table_delegate: &dyn TableDelegate,
+        display_columns: &[usize],
+    ) {
+        let Some(focus) = state.focus else {
+            return;
+        };
+        if let Some(edit) = &mut state.editing {
+            // Back to the editor:
+            if ui.input_mut(|i| i.consume_key(Modifiers::NONE, Key::F2)) {
+                edit.request_focus = true;
+            }
+            return;
+        }
+        let is_editable = display_columns
+            .get(focus.col_pos)
+            .is_some_and(|&col_nr| self.columns[col_nr].editable);
+        if !is_editable {
+            return;
+        }
+        // Pinned rows are also shown in the body, unless they are hidden there:
+        let placement = if self.is_hidden_in_body(focus.row_nr) {
+            RowPlacement::Pinned
+        } else {
+            RowPlacement::Body
+        };
+
+        let (f2, typed) = ui.input_mut(|i| {
+            let f2 = i.consume_key(Modifiers::NONE, Key::F2);
+            let mut typed = String::new();
+            i.events.retain(|event| match event {
+                egui::Event::Text(text) => {
+                    typed.push_str(text);
+                    false
+                }
+                _ => true,
+            });
+            (f2, typed)
+        });
+        if !typed.is_empty() {
+            // Typing replaces the value:
+            let value = Some(typed);
+            self.start_edit(
+                state,
+                table_delegate,
+                display_columns,
+                focus,
+                placement,
+                value,
+            );
+        } else if f2 {
+            self.start_edit(
+                state,
+                table_delegate,
+                display_columns,
+                focus,
+                placement,
+                None,
+            );
+        }
+    }
+
+    /// Find the rows to show, from [`Self::tree_column`], [`Self::row_mapping`],
+    /// [`Self::detail_rows`], and [`TableState::pinned_rows`].
+    ///
+    /// The per-row state is moved along with its rows, see [`TableDelegate::row_id`].
+    fn update_rows(
+        &mut self,
+        ctx: &Context,
+        id: Id,
+        state: &mut TableState,
+        table_delegate: &dyn TableDelegate,
+    ) {
+        if self.tree_column.is_some() {
+            debug_assert!(
+                self.row_mapping.is_none(),
+                "Table::row_mapping can't be combined with Table::tree_column"
+            );
+            let is_up_to_date = state.flat_tree.as_ref().is_some_and(|tree| {
+                tree.num_source_rows == self.num_rows && tree.generation == state.tree_generation
+            });
+            if !is_up_to_date {
+                let row_mapping = crate::tree::flatten(
+                    self.num_rows,
+                    |source_row| state.is_row_expanded(table_delegate.row_id(source_row)),
+                    |source_row| table_delegate.tree_node(source_row),
+                );
+                state.flat_tree = Some(FlatTree {
+                    num_source_rows: self.num_rows,
+                    generation: state.tree_generation,
+                    row_mapping: Arc::new(row_mapping),
+                });
+            }
+            self.row_mapping = state
+                .flat_tree
+                .as_ref()
+                .map(|tree| Arc::clone(&tree.row_mapping));
+        }
+
+        if let Some(row_mapping) = &self.row_mapping {
+            self.num_rows = row_mapping.len();
+        }
+
+        let moved = self.follow_row_ids(state, table_delegate);
+        self.follow_moved_row_heights(moved);
+
+        let mut detail_rows = std::mem::take(&mut self.detail_rows);
+        detail_rows.sort_unstable();
+        detail_rows.dedup();
+        let mut heights_above = 0.0;
+        self.details = detail_rows
+            .into_iter()
+            .filter(|&row_nr| row_nr < self.num_rows)
+            .filter_map(|row_nr| {
+                let height = table_delegate.row_detail_height(ctx, id, row_nr);
+                (0.0 < height).then(|| {
+                    let detail = (row_nr, height, heights_above);
+                    heights_above += f64::from(height);
+                    detail
+                })
+            })
+            .collect();
+
+        self.pinned_rows = state
+            .pinned_rows
+            .iter()
+            .filter(|&&row_nr| row_nr < self.num_rows)
+            .map(|&row_nr| {
+                let height = self.base_row_top_offset(ctx, id, table_delegate, row_nr + 1)
+                    - self.base_row_top_offset(ctx, id, table_delegate, row_nr);
+                (row_nr, to_f32(height))
+            })
+            .collect();
+
+        if state
+            .editing
+            .as_ref()
+            .is_some_and(|edit| self.num_rows <= edit.cell.row_nr)
+        {
+            state.editing = None;
+        }
+    }
+
+    /// The column index at each display position, skipping hidden columns.
+    ///
+    /// Also clamps the sticky columns to the shown ones, and converts [`Self::scroll_to_columns`] to display positions.
+    fn update_display_columns(&mut self, state: &TableState) -> Vec<usize> {
+        let display_columns: Vec<usize> = column_order(&self.columns, &state.col_order)
+            .into_iter()
+            .filter(|&col_nr| state.is_column_visible(self.columns[col_nr].id_for(col_nr)))
+            .collect();
+        self.num_sticky_cols = self.num_sticky_cols.at_most(display_columns.len());
+        self.num_sticky_cols_right = self
+            .num_sticky_cols_right
+            .at_most(display_columns.len() - self.num_sticky_cols);
+
+        if let Some((col_range, align)) = self.scroll_to_columns.take() {
+            // Convert to display positions:
+            let positions = || {
+                display_columns
+                    .iter()
+                    .enumerate()
+                    .filter(|(_, col_nr)| col_range.contains(col_nr))
+                    .map(|(col_pos, _)| col_pos)
+            };
+            if let (Some(min), Some(max)) = (positions().min(), positions().max()) {
+                self.scroll_to_columns = Some((min..=max, align));
+            }
+        }
+
+        display_columns
+    }
+
+    /// Load the column widths from the state, and auto-size them if needed.
+    fn update_column_widths(
+        &mut self,
+        ui: &Ui,
+        state: &mut TableState,
+        display_columns: &[usize],
+        do_full_sizing_pass: bool,
+    ) {
+        for (i, column) in self.columns.iter_mut().enumerate() {
+            let column_id = column.id_for(i);
+            if let Some(existing_width) = state.col_widths.get(&column_id) {
+                column.current = *existing_width;
+            }
+            column.current = column.range.clamp(column.current);
+
+            if do_full_sizing_pass {
+                column.auto_size_this_frame = true;
+            }
+        }
+
+        let parent_width = ui.available_width();
+        let auto_size = match self.auto_size_mode {
+            AutoSizeMode::Never => false,
+            AutoSizeMode::Always => true,
+            AutoSizeMode::OnParentResize => state.parent_width != Some(parent_width),
+        };
+        if auto_size {
+            // Only the shown columns take up space:
+            let mut columns: Vec<Column> = display_columns
+                .iter()
+                .map(|&col_nr| self.columns[col_nr])
+                .collect();
+            Column::auto_size(&mut columns, parent_width);
+            for (&col_nr, column) in display_columns.iter().zip(columns) {
+                self.columns[col_nr] = column;
+            }
+        }
+        state.parent_width = Some(parent_width);
+    }
+
+    /// Write all the rows of the table to `writer`, e.g. to export them to a file.
+    ///
+    /// Call this on a [`Table`] set up like the one you [`Self::show`], so it uses the same [`TableState`].
+    /// The rows are written in the order they are shown, see [`Self::row_mapping`] and [`Self::tree_column`],
+    /// and so are the columns, leaving out the hidden ones.
+    ///
+    /// The values come from [`TableDelegate::cell_text`], and the header from [`TableDelegate::header_cell_text`].
+    /// If there is no header text, the [`TableDelegate::column_name`]s are used.
+    ///
+    /// The rows are written in chunks, with a call to [`TableDelegate::prepare`] before each one,
+    /// so you only need to have one chunk of your data loaded at a time.
+    /// Nothing else is kept in memory, so this works for any number of rows.
+    ///
+    /// Returns the writer when done.
+    ///
+    /// # Errors
+    /// If writing fails.
+    pub fn export<W: io::Write>(
+        mut self,
+        ui: &Ui,
+        writer: W,
+        format: TextFormat,
+        table_delegate: &mut dyn TableDelegate,
+    ) -> io::Result<W> {
+        let id = TableState::id(ui, self.id_salt);
+        let mut state = TableState::load(ui.ctx(), id).unwrap_or_default();
+        self.update_rows(ui.ctx(), id, &mut state, table_delegate);
+        let display_columns = self.update_display_columns(&state);
+
+        let mut header_rows = self.header_texts(
+            id,
+            &state,
+            table_delegate,
+            &display_columns,
+            0..display_columns.len(),
+        );
+        if header_rows.is_empty() {
+            header_rows.push(
+                display_columns
+                    .iter()
+                    .map(|&col_nr| HeaderText::new(table_delegate.column_name(col_nr), 1))
+                    .collect(),
+            );
+        }
+
+        let mut writer = TableWriter::new(writer, format, display_columns.len(), &header_rows)?;
+        let mut row_nr = 0;
+        while row_nr < self.num_rows {
+            let rows = row_nr
+                ..row_nr
+                    .saturating_add(EXPORT_CHUNK_SIZE)
+                    .at_most(self.num_rows);
+            table_delegate.prepare(&PrefetchInfo {
+                num_sticky_columns: 0,
+                num_sticky_columns_right: 0,
+                visible_columns: 0..display_columns.len(),
+                column_order: display_columns.clone(),
+                visible_rows: rows.clone(),
+                pinned_rows: vec![],
+                sticky_section_header: None,
+                editing_cell: None,
+                table_id: id,
+            });
+            self.write_rows(&mut writer, rows.clone(), &display_columns, table_delegate)?;
+            row_nr = rows.end;
+        }
+        writer.finish()
+    }
+
+    pub fn show(mut self, ui: &mut Ui, table_delegate: &mut dyn TableDelegate) -> TableResponse {
+        let id = TableState::id(ui, self.id_salt);
+        let state = TableState::load(ui.ctx(), id);
+        let is_new = state.is_none();
+        let do_full_sizing_pass = is_new;
+        let mut state = state.unwrap_or_default();
+
+        if self.auto_row_heights {
+            self.row_heights = std::mem::take(&mut state.row_heights);
+            self.row_heights
+                .set_default_height(table_delegate.default_row_height());
+        }
+        self.update_rows(ui.ctx(), id, &mut state, table_delegate);
+
+        let body_height = self.body_row_top_offset(ui.ctx(), id, table_delegate, self.num_rows);
+        self.update_scroll_window(ui.ctx(), id, &mut state, table_delegate, body_height);
+        let old_scroll_offset = state.scroll_offset;
+
+        let display_columns = self.update_display_columns(&state);
+        let first_sticky_col_right = display_columns.len() - self.num_sticky_cols_right;
+
+        self.update_column_widths(ui, &mut state, &display_columns, do_full_sizing_pass);
+
+        let col_x = {
+            let mut x = ui.cursor().min.x;
+            let mut col_x = Vec1::with_capacity(x, display_columns.len() + 1);
+            for &col_nr in &display_columns {
+                x += self.columns[col_nr].current;
+                col_x.push(x);
+            }
+            col_x
+        };
+
+        let header_groups = self.header_groups(&display_columns);
+        let footer_groups = self.footer_groups(&display_columns);
+
+        let header_row_y = {
+            let mut y = ui.cursor().min.y;
+            let mut sticky_row_y = Vec1::with_capacity(y, self.headers.len() + 1);
+            for header in &self.headers {
+                y += header.height;
+                sticky_row_y.push(y);
+            }
+            sticky_row_y
+        };
+
+        let filter_row_y = self.filter_row.map(|height| {
+            let top = *header_row_y.last();
+            Rangef::new(top, top + height)
+        });
+
+        let pinned_row_y = {
+            let mut y = filter_row_y.map_or_else(|| *header_row_y.last(), |y_range| y_range.max);
+            let mut pinned_row_y = Vec1::with_capacity(y, self.pinned_rows.len() + 1);
+            for (_, height) in &self.pinned_rows {
+                y += height;
+                pinned_row_y.push(y);
+            }
+            pinned_row_y
+        };
+
+        let sticky_size = Vec2::new(
+            display_columns[..self.num_sticky_cols]
+                .iter()
+                .map(|&col_nr| self.columns[col_nr].current)
+                .sum(),
+            pinned_row_y.last() - header_row_y.first(),
+        );
+        let sticky_right_width: f32 = display_columns[first_sticky_col_right..]
+            .iter()
+            .map(|&col_nr| self.columns[col_nr].current)
+            .sum();
+        let footer_height: f32 = self.footers.iter().map(|f| f.height).sum();
 
         let mut ui_builder = UiBuilder::new().layout(Layout::top_down(Align::Min));
         if do_full_sizing_pass {
             ui_builder = ui_builder.sizing_pass().invisible();
             ui.ctx().request_discard("Full egui_table sizing");
         }
-        let response = ui
-            .scope_builder(ui_builder, |ui| {
-                // Don't wrap text in the table cells.
-                ui.style_mut().wrap_mode = Some(egui::TextWrapMode::Extend); // TODO: I think this is default for horizontal layouts anyway?
+        let mut selection_changed = false;
+        let mut measured_row_heights = BTreeMap::new();
+
+        let response = ui
+            .scope_builder(ui_builder, |ui| {
+                // Don't wrap text in the table cells.
+                ui.style_mut().wrap_mode = Some(egui::TextWrapMode::Extend); // TODO: I think this is default for horizontal layouts anyway?
+
+                let num_columns = self.columns.len();
+
+                for (col_nr, column) in self.columns.iter_mut().enumerate() {
+                    if column.resizable {
+                        let column_resize_id = id.with(column.id_for(col_nr)).with("resize");
+                        if let Some(response) = ui.ctx().read_response(column_resize_id)
+                            && response.double_clicked()
+                        {
+                            column.auto_size_this_frame = true;
+                        }
+                    }
+                    if column.auto_size_this_frame {
+                        ui.ctx().request_discard("egui_table column sizing");
+                    }
+                }
+
+                // The table takes keyboard focus when the user clicks it.
+                // This is registered first, so that it is behind everything else in the table.
+                let focus_response = ui.interact(
+                    Rect::from_min_size(ui.cursor().min, ui.available_size()),
+                    id,
+                    Sense::focusable_noninteractive(),
+                );
+                let has_focus = focus_response.has_focus();
+                if has_focus && self.keyboard_navigation && !do_full_sizing_pass {
+                    let view_height = ui.available_height() - sticky_size.y - footer_height;
+                    selection_changed |= self.navigate_with_keys(
+                        ui,
+                        id,
+                        &mut state,
+                        table_delegate,
+                        display_columns.len(),
+                        view_height,
+                    );
+                }
+                if has_focus && !do_full_sizing_pass {
+                    self.start_edit_with_keys(ui, &mut state, table_delegate, &display_columns);
+                }
+                if let Some(edit) = &mut state.editing
+                    && std::mem::take(&mut edit.scroll_to)
+                {
+                    self.scroll_to_cell(edit.cell, display_columns.len());
+                }
+
+                let scroll_outer_size =
+                    (ui.available_size() - sticky_size - vec2(sticky_right_width, footer_height))
+                        .at_least(Vec2::ZERO);
+                let fixed_right_x = ui.cursor().min.x + sticky_size.x + scroll_outer_size.x;
+
+                if let Some((row_range, _)) = &self.scroll_to_rows {
+                    let top =
+                        self.body_row_top_offset(ui.ctx(), id, table_delegate, *row_range.start());
+                    if top < self.scroll_origin || self.scroll_origin + MAX_SCROLL_HEIGHT <= top {
+                        // Too far away to scroll to, so jump there first:
+                        self.jump_scroll_window(&mut state, body_height, top);
+                    }
+                }
+
+                SplitScroll {
+                    scroll_enabled: Vec2b::new(true, true),
+                    fixed_size: sticky_size,
+                    fixed_right_width: sticky_right_width,
+                    fixed_bottom_height: footer_height,
+                    scroll_outer_size,
+                    scroll_content_size: Vec2::new(
+                        display_columns[self.num_sticky_cols..first_sticky_col_right]
+                            .iter()
+                            .map(|&col_nr| self.columns[col_nr].current)
+                            .sum(),
+                        to_f32((body_height - self.scroll_origin).at_most(MAX_SCROLL_HEIGHT)),
+                    ),
+                    stick_to_bottom: self.stick_to_bottom,
+                    // Keep the rows in view in place, e.g. if the rows above them changed height:
+                    vertical_scroll_shift: std::mem::take(&mut state.scroll_correction),
+                }
+                .show(
+                    ui,
+                    &mut TableSplitScrollDelegate {
+                        id,
+                        table_delegate,
+                        state: &mut state,
+                        table: &mut self,
+                        display_columns: &display_columns,
+                        col_x,
+                        header_row_y,
+                        filter_row_y,
+                        pinned_row_y,
+                        header_groups,
+                        footer_groups,
+                        max_column_widths: vec![0.0; num_columns],
+                        visible_column_lines: Default::default(),
+                        do_full_sizing_pass,
+                        has_prefetched: false,
+                        egui_ctx: ui.ctx().clone(),
+                        selection_changed: &mut selection_changed,
+                        has_focus,
+                        scroll_offset: Vec2::ZERO,
+                        fixed_right_x,
+                        column_drag: None,
+                        region_columns: 0..0,
+                        is_editor_shown: false,
+                        measured_row_heights: &mut measured_row_heights,
+                    },
+                );
+
+                if has_focus {
+                    self.clipboard_ui(ui, id, &state, table_delegate, &display_columns);
+                }
+            })
+            .response;
+
+        state.scroll_anchor = self.scroll_anchor(ui.ctx(), id, &state, table_delegate);
+        if self.auto_row_heights {
+            self.update_row_heights(
+                ui.ctx(),
+                id,
+                &mut state,
+                table_delegate,
+                measured_row_heights,
+            );
+        }
+
+        let is_scrolling = state.scroll_offset != old_scroll_offset
+            || self.scroll_to_rows.is_some()
+            || ui.input(|i| i.pointer.any_down());
+        if !is_scrolling {
+            self.follow_view_with_scroll_window(&mut state, body_height);
+        }
+
+        self.remember_row_ids(&mut state, table_delegate);
+        state.display_columns = display_columns;
+        state.store(ui.ctx(), id);
+
+        TableResponse {
+            response,
+            selection_changed,
+        }
+    }
+}
+
+/// Where the view is, relative to the row at the top of it.
+///
+/// See [`TableDelegate::row_id`].
+#[derive(Clone, Copy, Debug)]
+pub(crate) struct ScrollAnchor {
+    /// The [`TableDelegate::row_id`] of the row.
+    row_id: Id,
+
+    row_nr: u64,
+
+    /// How far below the top of the row the view starts.
+    offset_in_row: f32,
+
+    /// [`Table::num_rows`] at the time.
+    num_rows: u64,
+}
+
+/// A cell being edited, see [`TableDelegate::cell_editor_ui`].
+#[derive(Clone, Debug)]
+pub(crate) struct CellEdit {
+    cell: CellCoord,
+
+    /// Where the editor is shown, since pinned rows can be shown twice.
+    placement: RowPlacement,
+
+    /// What is in the editor.
+    value: String,
+
+    /// Why [`TableDelegate::commit_edit`] rejected the value.
+    error: Option<String>,
+
+    /// Should the editor take the keyboard focus?
+    request_focus: bool,
+
+    /// Should we scroll to the cell?
+    scroll_to: bool,
+}
+
+/// The [`TableDelegate::row_id`] of the rows that [`TableState`] refers to by row number.
+#[derive(Clone, Debug, Default)]
+pub(crate) struct TrackedRows {
+    /// [`Table::num_rows`] at the time.
+    num_rows: u64,
+
+    /// [`Table::row_mapping`] at the time.
+    row_mapping: Option<Weak<RowMapping>>,
+
+    /// [`TableState::row_ids_generation`] at the time.
+    generation: u64,
+
+    ids: BTreeMap<u64, Id>,
+
+    /// Are all the selected rows in [`Self::ids`]?
+    ///
+    /// Not if there were too many of them, see [`MAX_TRACKED_SELECTED_ROWS`].
+    has_selected_rows: bool,
+}
+
+impl TrackedRows {
+    /// Are the rows of the table the same as when these were tracked?
+    ///
+    /// Rows are assumed to stay put unless the number of rows or the row mapping changes,
+    /// see [`TableState::refresh_row_ids`].
+    fn is_up_to_date(&self, table: &Table, generation: u64) -> bool {
+        let same_row_mapping = match (&self.row_mapping, &table.row_mapping) {
+            (None, None) => true,
+            // The allocation is kept alive by the `Weak`, so the pointer can't be reused:
+            (Some(old), Some(new)) => std::ptr::eq(old.as_ptr(), Arc::as_ptr(new)),
+            _ => false,
+        };
+        self.num_rows == table.num_rows && self.generation == generation && same_row_mapping
+    }
+}
+
+/// How the rows moved since last frame, see [`Table::follow_row_ids`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum MovedRows {
+    /// All rows are where they were.
+    None,
+
+    /// Rows were added or removed above, moving all rows by the same amount.
+    Shifted { old_num_rows: u64 },
+
+    /// The rows were sorted or filtered, or moved in some other way.
+    Reordered,
+}
+
+/// Where a row is now, if `old_num_rows` became `new_num_rows` by adding or removing rows above it.
+fn shifted_row_nr(row_nr: u64, old_num_rows: u64, new_num_rows: u64) -> u64 {
+    if old_num_rows <= new_num_rows {
+        row_nr.saturating_add(new_num_rows - old_num_rows)
+    } else {
+        row_nr.saturating_sub(old_num_rows - new_num_rows)
+    }
+}
+
+/// A header cell, covering one or more columns.
+#[derive(Clone, Debug)]
+struct HeaderGroup {
+    group_index: usize,
+
+    /// Column indices.
+    col_range: Range<usize>,
+
+    /// Display positions.
+    col_pos_range: Range<usize>,
+}
+
+/// The header (or footer) cells of one row, in display order.
+///
+/// `groups` are ranges of column indices, as in [`HeaderRow::groups`].
+/// If empty, each shown column gets its own cell.
+fn group_columns(groups: &[Range<usize>], display_columns: &[usize]) -> Vec<HeaderGroup> {
+    if groups.is_empty() {
+        display_columns
+            .iter()
+            .enumerate()
+            .map(|(col_pos, &col_nr)| HeaderGroup {
+                group_index: col_nr,
+                col_range: col_nr..col_nr + 1,
+                col_pos_range: col_pos..col_pos + 1,
+            })
+            .collect()
+    } else {
+        let mut groups: Vec<HeaderGroup> = groups
+            .iter()
+            .enumerate()
+            .filter_map(|(group_index, col_range)| {
+                let positions = || {
+                    display_columns
+                        .iter()
+                        .enumerate()
+                        .filter(|(_, col_nr)| col_range.contains(col_nr))
+                        .map(|(col_pos, _)| col_pos)
+                };
+                Some(HeaderGroup {
+                    group_index,
+                    col_range: col_range.clone(),
+                    col_pos_range: positions().min()?..positions().max()? + 1,
+                })
+            })
+            .collect();
+        groups.sort_by_key(|group| group.col_pos_range.start);
+        groups
+    }
+}
+
+/// The user is dragging a column header to move the column.
+#[derive(Clone, Copy, Debug)]
+struct ColumnDrag {
+    /// Where the column would be dropped: before this display position.
+    drop_pos: usize,
+
+    /// Would dropping the column here keep all header groups together?
+    is_valid: bool,
+}
+
+#[derive(Clone, Copy, Debug)]
+struct ColumnResizer {
+    scroll_offset: Vec2,
+
+    top: f32,
+}
+
+fn update(map: &mut BTreeMap<usize, ColumnResizer>, key: usize, value: ColumnResizer) {
+    match map.entry(key) {
+        Entry::Vacant(entry) => {
+            entry.insert(value);
+        }
+        Entry::Occupied(mut entry) => {
+            entry.get_mut().top = entry.get_mut().top.min(value.top);
+        }
+    }
+}
+
+/// Where a row of data is shown.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+enum RowPlacement {
+    /// At its usual place in the body.
+    Body,
+
+    /// Pinned below the header, see [`TableState::pinned_rows`].
+    Pinned,
+
+    /// A section header stuck to the top of the body, see [`TableDelegate::section_header_row`].
+    StickySection,
+}
+
+struct TableSplitScrollDelegate<'a> {
+    id: Id,
+    table_delegate: &'a mut dyn TableDelegate,
+    table: &'a mut Table,
+    state: &'a mut TableState,
+
+    /// The column index at each display position.
+    display_columns: &'a [usize],
+
+    /// The x coordinate for the start of each column, plus the end of the last column.
+    ///
+    /// Indexed by display position.
+    col_x: Vec1<f32>,
+
+    /// The y coordinate for the start of each header row, plus the end of the last header row.
+    header_row_y: Vec1<f32>,
+
+    /// The y range of the [`Table::filter_row`], if shown, right below the header rows.
+    filter_row_y: Option<Rangef>,
+
+    /// The y coordinate for the start of each pinned row, plus the end of the last pinned row.
+    ///
+    /// The first value is the end of the header and filter rows, and the last is the top of the body.
+    pinned_row_y: Vec1<f32>,
+
+    /// The header cells of each header row.
+    header_groups: Vec<Vec<HeaderGroup>>,
+
+    /// The footer cells of each footer row.
+    footer_groups: Vec<Vec<HeaderGroup>>,
+
+    /// Actual width of the widest element in each column
+    max_column_widths: Vec<f32>,
+
+    /// Key is display position. The resizer is to the right of the column.
+    visible_column_lines: BTreeMap<usize, ColumnResizer>,
+
+    do_full_sizing_pass: bool,
+
+    has_prefetched: bool,
+
+    egui_ctx: Context,
+
+    /// Set if the user changed the selection.
+    selection_changed: &'a mut bool,
+
+    /// Does the table have keyboard focus?
+    has_focus: bool,
+
+    /// The scroll offset of the scrollable region, set by `right_bottom_ui`.
+    scroll_offset: Vec2,
+
+    /// The screen x coordinate where the sticky columns on the right start.
+    fixed_right_x: f32,
+
+    /// Set while the user is dragging a column header.
+    column_drag: Option<ColumnDrag>,
+
+    /// The display positions of the region being shown: the sticky columns on either side, or the scrolled ones.
+    ///
+    /// The editor of [`TableState::editing_cell`] is only shown in the region of its column,
+    /// and the footer cells only in the regions they overlap.
+    region_columns: Range<usize>,
+
+    /// Has the editor been shown this frame?
+    is_editor_shown: bool,
+
+    /// The height of the contents of each row shown this frame.
+    ///
+    /// Only filled in if [`Table::auto_row_heights`] is turned on.
+    measured_row_heights: &'a mut BTreeMap<u64, f32>,
+}
+
+impl TableSplitScrollDelegate<'_> {
+    /// Helper wrapper around [`Table::get_row_top_offset`].
+    fn get_row_top_offset(&self, row_nr: u64) -> f32 {
+        self.table
+            .get_row_top_offset(&self.egui_ctx, self.id, self.table_delegate, row_nr)
+    }
+
+    /// Helper wrapper around [`Table::get_row_nr_at_y_offset`].
+    fn get_row_nr_at_y_offset(&self, y_offset: f32) -> u64 {
+        self.table
+            .get_row_nr_at_y_offset(&self.egui_ctx, self.id, self.table_delegate, y_offset)
+    }
+
+    /// The height of the row itself, without any detail panel below it.
+    fn row_height(&self, row_nr: u64) -> f32 {
+        let offset = |row_nr| {
+            self.table
+                .base_row_top_offset(&self.egui_ctx, self.id, self.table_delegate, row_nr)
+        };
+        to_f32(offset(row_nr + 1) - offset(row_nr))
+    }
+
+    /// The y coordinate of the top of the body, below the header and any pinned rows.
+    fn body_top(&self) -> f32 {
+        *self.pinned_row_y.last()
+    }
+
+    /// The first display position of the sticky columns on the right.
+    fn first_sticky_col_right(&self) -> usize {
+        self.display_columns.len() - self.table.num_sticky_cols_right
+    }
+
+    /// How far the sticky columns on the right are offset from their place in [`Self::col_x`].
+    ///
+    /// They are shown at the right edge of the table, no matter the horizontal scroll.
+    fn sticky_right_offset(&self) -> f32 {
+        self.col_x[self.first_sticky_col_right()] - self.fixed_right_x
+    }
+
+    /// The horizontal offset to add to a screen x coordinate to get the [`Self::col_x`] coordinate.
+    fn x_offset_at(&self, x: f32) -> f32 {
+        if x < self.col_x[self.table.num_sticky_cols] {
+            0.0
+        } else if 0 < self.table.num_sticky_cols_right && self.fixed_right_x <= x {
+            self.sticky_right_offset()
+        } else {
+            self.scroll_offset.x
+        }
+    }
+
+    fn header_ui(&mut self, ui: &mut Ui, scroll_offset: Vec2) {
+        let header_groups = std::mem::take(&mut self.header_groups);
+
+        for (row_nr, groups) in header_groups.iter().enumerate() {
+            let y_range = Rangef::new(self.header_row_y[row_nr], self.header_row_y[row_nr + 1]);
+
+            for group in groups {
+                let HeaderGroup {
+                    group_index,
+                    col_range,
+                    col_pos_range,
+                } = group.clone();
+                let start = col_pos_range.start;
+                let end = col_pos_range.end;
+
+                let mut header_rect =
+                    Rect::from_x_y_ranges(self.col_x[start]..=self.col_x[end], y_range)
+                        .translate(-scroll_offset);
+
+                // The previous column scrolls, so its right edge is usually hidden behind this one:
+                let is_after_scrollable_columns =
+                    0 < self.table.num_sticky_cols_right && start == self.first_sticky_col_right();
+
+                if 0 < start
+                    && !is_after_scrollable_columns
+                    && self.table.columns[self.display_columns[start - 1]].resizable
+                    && ui.clip_rect().x_range().contains(header_rect.left())
+                {
+                    // The previous column is resizable, so make sure the resize line goes to above this heading:
+                    update(
+                        &mut self.visible_column_lines,
+                        start - 1,
+                        ColumnResizer {
+                            scroll_offset,
+                            top: header_rect.top(),
+                        },
+                    );
+                }
+
+                let clip_rect = header_rect;
+
+                let last_column = &self.table.columns[self.display_columns[end - 1]];
+                let auto_size_this_frame = last_column.auto_size_this_frame; // TODO: correct?
+
+                if auto_size_this_frame {
+                    // Note: we shrink the cell rect when auto-sizing, but not the clip rect! This is to avoid flicker.
+                    header_rect.max.x = header_rect.min.x
+                        + self.display_columns[start..end]
+                            .iter()
+                            .map(|&col_nr| self.table.columns[col_nr].range.min)
+                            .sum::<f32>();
+                }
+
+                let is_single_column = start + 1 == end;
+                let reorderable =
+                    is_single_column && self.table.columns[self.display_columns[start]].reorderable;
+
+                // Only the header row closest to the body sorts:
+                let sort_column_id = if is_single_column
+                    && row_nr + 1 == header_groups.len()
+                    && self.table.columns[self.display_columns[start]].sortable
+                {
+                    let col_nr = self.display_columns[start];
+                    Some(self.table.columns[col_nr].id_for(col_nr))
+                } else {
+                    None
+                };
+
+                let mut ui_builder = UiBuilder::new()
+                    .max_rect(header_rect)
+                    .id_salt(("header", row_nr, group_index))
+                    .layout(egui::Layout::left_to_right(egui::Align::Center));
+                if auto_size_this_frame {
+                    ui_builder = ui_builder.sizing_pass();
+                }
+                let mut sense = Sense::hover();
+                if reorderable {
+                    sense |= Sense::drag();
+                }
+                if self.table.column_chooser || sort_column_id.is_some() {
+                    sense |= Sense::click();
+                }
+                let mut cell_ui = ui.new_child(ui_builder.sense(sense));
+                cell_ui.shrink_clip_rect(clip_rect);
+
+                if reorderable {
+                    self.column_drag_ui(&cell_ui, start, clip_rect);
+                }
+                if let Some(column_id) = sort_column_id
+                    && cell_ui.response().clicked()
+                {
+                    let modifiers = cell_ui.input(|i| i.modifiers);
+                    self.state.sort.on_click(column_id, modifiers);
+                    self.table_delegate.on_sort_changed(&self.state.sort);
+
+                    // The body was already painted with the old sort:
+                    cell_ui.ctx().request_discard("egui_table sort changed");
+                }
+                let sort_direction =
+                    sort_column_id.and_then(|column_id| self.state.sort.direction(column_id));
+                if self.table.column_chooser {
+                    egui::Popup::context_menu(&cell_ui.response())
+                        .close_behavior(egui::PopupCloseBehavior::CloseOnClickOutside)
+                        .show(|ui| self.column_chooser_ui(ui));
+                }
+
+                self.table_delegate.header_cell_ui(
+                    &mut cell_ui,
+                    &HeaderCellInfo {
+                        group_index,
+                        col_range,
+                        col_pos_range,
+                        row_nr,
+                        sort_direction,
+                        table_id: self.id,
+                    },
+                );
+
+                if let Some(column_id) = sort_column_id
+                    && let Some(direction) = sort_direction
+                {
+                    let priority = self.state.sort.priority(column_id).unwrap_or_default();
+                    self.table_delegate.sort_indicator_ui(
+                        &mut cell_ui,
+                        direction,
+                        priority,
+                        self.state.sort.keys.len(),
+                    );
+                }
+
+                if is_single_column {
+                    // normal single-column group
+                    let col_pos = start;
+                    let col_nr = self.display_columns[col_pos];
+                    let column = &self.table.columns[col_nr];
+                    let width = &mut self.max_column_widths[col_nr];
+                    *width = width.max(cell_ui.min_size().x);
+
+                    // Save column lines for later interaction:
+                    if column.resizable && ui.clip_rect().x_range().contains(header_rect.right()) {
+                        update(
+                            &mut self.visible_column_lines,
+                            col_pos,
+                            ColumnResizer {
+                                scroll_offset,
+                                top: header_rect.top(),
+                            },
+                        );
+                    }
+                }
+            }
+        }
+
+        self.header_groups = header_groups;
+    }
+
+    /// The [`Table::filter_row`], with the filter widget of each column in the current region.
+    fn filter_row_ui(&mut self, ui: &mut Ui, scroll_offset: Vec2) {
+        let Some(y_range) = self.filter_row_y else {
+            return;
+        };
+
+        for col_pos in self.region_columns.clone() {
+            let col_nr = self.display_columns[col_pos];
+            let Some(filter) = self.table_delegate.column_filter(col_nr) else {
+                continue;
+            };
+            let cell_rect =
+                Rect::from_x_y_ranges(self.col_x[col_pos]..=self.col_x[col_pos + 1], y_range)
+                    .translate(-scroll_offset);
+            if !ui.clip_rect().intersects(cell_rect) {
+                continue;
+            }
+
+            let mut cell_ui = ui.new_child(
+                UiBuilder::new()
+                    .max_rect(cell_rect)
+                    .id_salt(("filter", col_nr))
+                    .layout(egui::Layout::left_to_right(egui::Align::Center)),
+            );
+            cell_ui.shrink_clip_rect(cell_rect);
+
+            let column_id = self.table.columns[col_nr].id_for(col_nr);
+            let mut value = self
+                .state
+                .filters
+                .get(column_id)
+                .filter(|value| {
+                    // Ignore values stored for another kind of filter:
+                    std::mem::discriminant(*value)
+                        == std::mem::discriminant(&FilterValue::empty(&filter))
+                })
+                .cloned()
+                .unwrap_or_else(|| FilterValue::empty(&filter));
+            // Note: we don't measure the widget, since it fills the column.
+            if self
+                .table_delegate
+                .filter_ui(&mut cell_ui, col_nr, &filter, &mut value)
+            {
+                self.state.filters.set(column_id, value);
+                self.table_delegate.on_filters_changed(&self.state.filters);
+
+                // The body was already painted with the old filters:
+                cell_ui.ctx().request_discard("egui_table filters changed");
+            }
+        }
+    }
+
+    /// The footer rows, stuck to the bottom of the table.
+    ///
+    /// `x_offset` is the horizontal scroll offset of the footer region.
+    fn footer_ui(&mut self, ui: &mut Ui, x_offset: f32) {
+        let footer_groups = std::mem::take(&mut self.footer_groups);
+
+        let mut y = ui.max_rect().top();
+        for (row_nr, groups) in footer_groups.iter().enumerate() {
+            let height = self.table.footers[row_nr].height;
+            let y_range = Rangef::new(y, y + height);
+            y += height;
+
+            for group in groups {
+                let HeaderGroup {
+                    group_index,
+                    col_range,
+                    col_pos_range,
+                } = group.clone();
+                let start = col_pos_range.start;
+                let end = col_pos_range.end;
+                if end <= self.region_columns.start || self.region_columns.end <= start {
+                    continue; // Shown in another region
+                }
+
+                let mut cell_rect =
+                    Rect::from_x_y_ranges(self.col_x[start]..=self.col_x[end], y_range)
+                        .translate(vec2(-x_offset, 0.0));
+                let clip_rect = cell_rect;
+
+                let is_single_column = start + 1 == end;
+                let auto_size_this_frame = is_single_column
+                    && self.table.columns[self.display_columns[start]].auto_size_this_frame;
+                if auto_size_this_frame {
+                    // Note: we shrink the cell rect when auto-sizing, but not the clip rect! This is to avoid flicker.
+                    cell_rect.max.x =
+                        cell_rect.min.x + self.table.columns[self.display_columns[start]].range.min;
+                }
+
+                let mut ui_builder = UiBuilder::new()
+                    .max_rect(cell_rect)
+                    .id_salt(("footer", row_nr, group_index))
+                    .layout(egui::Layout::left_to_right(egui::Align::Center));
+                if auto_size_this_frame {
+                    ui_builder = ui_builder.sizing_pass();
+                }
+                let mut cell_ui = ui.new_child(ui_builder);
+                cell_ui.shrink_clip_rect(clip_rect);
+
+                self.table_delegate.footer_cell_ui(
+                    &mut cell_ui,
+                    &FooterCellInfo {
+                        group_index,
+                        col_range,
+                        col_pos_range,
+                        row_nr,
+                        table_id: self.id,
+                    },
+                );
+
+                if is_single_column {
+                    let width = &mut self.max_column_widths[self.display_columns[start]];
+                    *width = width.max(cell_ui.min_size().x);
+                }
+            }
+        }
+
+        self.footer_groups = footer_groups;
+    }
+
+    /// Lets the user hide and show columns.
+    fn column_chooser_ui(&mut self, ui: &mut Ui) {
+        let num_visible = self.display_columns.len();
+        for col_nr in column_order(&self.table.columns, &self.state.col_order) {
+            let column_id = self.table.columns[col_nr].id_for(col_nr);
+            let mut visible = self.state.is_column_visible(column_id);
+            let is_last_visible = visible && num_visible <= 1; // Don't hide all columns
+            let checkbox =
+                egui::Checkbox::new(&mut visible, self.table_delegate.column_name(col_nr));
+            if ui.add_enabled(!is_last_visible, checkbox).changed() {
+                self.state.set_column_visible(column_id, visible);
+            }
+        }
+
+        ui.separator();
+
+        if ui
+            .add_enabled(
+                num_visible < self.table.columns.len(),
+                egui::Button::new("Show all"),
+            )
+            .clicked()
+        {
+            self.state.col_visible.clear();
+        }
+    }
+
+    /// Where would a column be dropped if released at this screen x coordinate?
+    ///
+    /// Returns the display position to insert the column before.
+    fn column_drop_pos(&self, x: f32) -> usize {
+        let num_sticky_cols = self.table.num_sticky_cols;
+        let first_sticky_col_right = self.first_sticky_col_right();
+        let positions = if x < self.col_x[num_sticky_cols] {
+            0..=num_sticky_cols
+        } else if 0 < self.table.num_sticky_cols_right && self.fixed_right_x <= x {
+            first_sticky_col_right..=self.display_columns.len()
+        } else {
+            num_sticky_cols..=first_sticky_col_right
+        };
+        let x = x + self.x_offset_at(x);
+        positions
+            .min_by(|&a, &b| {
+                let dist_a = (self.col_x[a] - x).abs();
+                let dist_b = (self.col_x[b] - x).abs();
+                dist_a.total_cmp(&dist_b)
+            })
+            .unwrap_or_default()
+    }
+
+    /// Would moving the column at `col_pos` to before `drop_pos` do anything,
+    /// while still keeping all header groups together?
+    fn is_valid_column_move(&self, col_pos: usize, drop_pos: usize) -> bool {
+        if drop_pos == col_pos || drop_pos == col_pos + 1 {
+            return false; // no change
+        }
+        let mut order = self.display_columns.to_vec();
+        move_column(
+            &mut order,
+            self.display_columns[col_pos],
+            self.display_columns.get(drop_pos).copied(),
+        );
+        self.table
+            .headers
+            .iter()
+            .all(|header_row| groups_are_contiguous(&order, &header_row.groups))
+    }
+
+    /// Drag a column header to move the column.
+    fn column_drag_ui(&mut self, cell_ui: &Ui, col_pos: usize, cell_rect: Rect) {
+        let response = cell_ui.response();
+        if !response.dragged() && !response.drag_stopped() {
+            return;
+        }
+        let Some(pointer_pos) = cell_ui.ctx().pointer_latest_pos() else {
+            return;
+        };
+
+        let drop_pos = self.column_drop_pos(pointer_pos.x);
+        let is_valid = self.is_valid_column_move(col_pos, drop_pos);
+
+        if response.drag_stopped() {
+            if is_valid {
+                let moved = self.display_columns[col_pos];
+                let before = self.display_columns.get(drop_pos).copied();
+                let mut order = column_order(&self.table.columns, &self.state.col_order);
+                move_column(&mut order, moved, before);
+                self.state.col_order = order
+                    .iter()
+                    .map(|&col_nr| self.table.columns[col_nr].id_for(col_nr))
+                    .collect();
+
+                let mut display_columns = self.display_columns.to_vec();
+                move_column(&mut display_columns, moved, before);
+                self.state
+                    .follow_moved_columns(self.display_columns, &display_columns);
+            }
+        } else {
+            cell_ui.ctx().set_cursor_icon(egui::CursorIcon::Grabbing);
+            cell_ui.painter().rect_filled(
+                cell_rect,
+                0.0,
+                cell_ui.visuals().widgets.active.weak_bg_fill,
+            );
+            self.column_drag = Some(ColumnDrag { drop_pos, is_valid });
+        }
+    }
+
+    /// Which column is at the given screen x coordinate?
+    ///
+    /// The position is clamped to the table, and there must be at least one column.
+    fn col_pos_at(&self, x: f32, scroll_offset: Vec2) -> usize {
+        // The sticky columns don't scroll horizontally:
+        let x = if x < self.col_x[self.table.num_sticky_cols] {
+            x
+        } else if 0 < self.table.num_sticky_cols_right && self.fixed_right_x <= x {
+            x + self.sticky_right_offset()
+        } else {
+            x + scroll_offset.x
+        };
+        self.col_x
+            .partition_point(|&col_x| col_x <= x)
+            .saturating_sub(1)
+            .at_most(self.display_columns.len() - 1)
+    }
+
+    /// Which body cell is at the given screen position?
+    ///
+    /// The position is clamped to the table body, so this only returns `None` for an empty table.
+    fn cell_at_pos(&self, pos: Pos2, scroll_offset: Vec2) -> Option<CellCoord> {
+        if self.display_columns.is_empty() || self.table.num_rows == 0 {
+            return None;
+        }
+
+        let col_pos = self.col_pos_at(pos.x, scroll_offset);
+
+        let y = pos.y + scroll_offset.y - self.body_top();
+        let row_nr = self
+            .get_row_nr_at_y_offset(y.at_least(0.0))
+            .at_most(self.table.num_rows - 1);
+
+        Some(CellCoord { row_nr, col_pos })
+    }
+
+    /// Handle clicks on a row, and paint the selection highlight.
+    ///
+    /// Double-clicking a cell starts editing it, see [`Column::editable`].
+    fn row_selection_ui(
+        &mut self,
+        row_ui: &Ui,
+        row_nr: u64,
+        row_rect: Rect,
+        scroll_offset: Vec2,
+        placement: RowPlacement,
+    ) {
+        let selection_mode = self.table.selection_mode;
+        let response = row_ui.response();
+
+        let cell_at_pointer = || {
+            let pos = response.interact_pointer_pos()?;
+            if placement == RowPlacement::Body {
+                self.cell_at_pos(pos, scroll_offset)
+            } else {
+                // The row is not where it is in the body:
+                Some(CellCoord {
+                    row_nr,
+                    col_pos: self.col_pos_at(pos.x, scroll_offset),
+                })
+            }
+        };
+
+        let pressed =
+            response.is_pointer_button_down_on() && row_ui.input(|i| i.pointer.primary_pressed());
+        let pressed_cell = if pressed { cell_at_pointer() } else { None };
+        let double_clicked_cell = if response.double_clicked() {
+            cell_at_pointer()
+        } else {
+            None
+        };
+
+        if let Some(cell) = pressed_cell {
+            // Move the keyboard focus to the pressed cell:
+            self.state.focus = Some(cell);
+            row_ui.memory_mut(|mem| mem.request_focus(self.id));
+        }
+        if response.clicked() {
+            // egui takes the focus away from the table when the click ends on the row instead:
+            row_ui.memory_mut(|mem| mem.request_focus(self.id));
+        }
+
+        if let Some(cell) = double_clicked_cell {
+            self.table.start_edit(
+                self.state,
+                self.table_delegate,
+                self.display_columns,
+                cell,
+                placement,
+                None,
+            );
+        }
+
+        if selection_mode == SelectionMode::None {
+            return;
+        }
+
+        let old_selection = self.state.selection.clone();
+
+        if selection_mode == SelectionMode::Cells {
+            if let Some(cell) = pressed_cell {
+                let modifiers = row_ui.input(|i| i.modifiers);
+                self.state.selection.on_cell_press(cell, modifiers);
+                self.state.is_drag_selecting = true;
+            }
+        } else {
+            if response.clicked() {
+                let modifiers = row_ui.input(|i| i.modifiers);
+                self.state
+                    .selection
+                    .on_click(selection_mode, row_nr, modifiers);
+            }
+
+            if self.state.selection.is_row_selected(row_nr) {
+                row_ui
+                    .painter()
+                    .rect_filled(row_rect, 0.0, row_ui.visuals().selection.bg_fill);
+            }
+        }
+
+        *self.selection_changed |= self.state.selection != old_selection;
+    }
+
+    /// Extend the cell selection to wherever the pointer is dragged,
+    /// autoscrolling when the pointer is close to the edge of the table body.
+    ///
+    /// Must be called from within the scroll area.
+    fn drag_selection_ui(&mut self, ui: &Ui, scroll_offset: Vec2) {
+        /// Start autoscrolling when the pointer is this close to the edge.
+        const AUTOSCROLL_MARGIN: f32 = 16.0;
+
+        if !self.state.is_drag_selecting {
+            return;
+        }
+        if !ui.input(|i| i.pointer.primary_down()) {
+            self.state.is_drag_selecting = false;
+            return;
+        }
+        let Some(pointer_pos) = ui.ctx().pointer_latest_pos() else {
+            return;
+        };
+
+        // The scrollable part of the body:
+        let scroll_rect = ui.clip_rect();
+
+        let body_rect = Rect::from_min_max(
+            egui::pos2(*self.col_x.first(), scroll_rect.top()),
+            scroll_rect.max
+                + vec2(
+                    self.col_x.last() - self.col_x[self.first_sticky_col_right()],
+                    0.0,
+                ),
+        );
+
+        let edge_distance = |pos: f32, min: f32, max: f32| -> f32 {
+            if pos < min + AUTOSCROLL_MARGIN {
+                (min + AUTOSCROLL_MARGIN - pos).at_most(100.0)
+            } else if max - AUTOSCROLL_MARGIN < pos {
+                -(pos - (max - AUTOSCROLL_MARGIN)).at_most(100.0)
+            } else {
+                0.0
+            }
+        };
+        let autoscroll = vec2(
+            edge_distance(pointer_pos.x, body_rect.left(), body_rect.right()),
+            edge_distance(pointer_pos.y, body_rect.top(), body_rect.bottom()),
+        );
+        if autoscroll != Vec2::ZERO {
+            let dt = ui.input(|i| i.stable_dt).at_most(0.1);
+            ui.scroll_with_delta_animation(
+                20.0 * dt * autoscroll,
+                egui::style::ScrollAnimation::none(),
+            );
+            ui.ctx().request_repaint();
+        }
+
+        let pos = body_rect.shrink(0.5).clamp(pointer_pos);
+        if let Some(cell) = self.cell_at_pos(pos, scroll_offset)
+            && let Some(cells) = &mut self.state.selection.cells
+            && cells.focus != cell
+        {
+            cells.focus = cell;
+            *self.selection_changed = true;
+        }
+    }
+
+    /// The section header row to stick to the top of the viewport, and where to show it (before scrolling).
+    ///
+    /// `row_range` is the rows visible in the viewport.
+    fn sticky_section_header(
+        &self,
+        viewport: Rect,
+        row_range: &Range<u64>,
+    ) -> Option<(u64, Rangef)> {
+        if row_range.is_empty() || self.do_full_sizing_pass {
+            return None;
+        }
+
+        let top_row = self
+            .get_row_nr_at_y_offset(viewport.top() - self.body_top())
+            .at_most(self.table.num_rows - 1);
+        let header_row = self.table_delegate.section_header_row(top_row)?;
+
+        let row_y = |row_nr: u64| self.body_top() + self.get_row_top_offset(row_nr);
+        if viewport.top() <= row_y(header_row) {
+            return None; // Already in view where it is
+        }
+
+        let height = self.row_height(header_row);
+        if height <= 0.0 {
+            return None;
+        }
+        let mut top = viewport.top();
+
+        // The header of the next section pushes it out:
+        let next_header = (top_row + 1..row_range.end).find(|&row_nr| {
+            self.table_delegate.section_header_row(row_nr) == Some(row_nr)
+                && !self.table.is_hidden_in_body(row_nr)
+        });
+        if let Some(next_header) = next_header {
+            top = top.at_most(row_y(next_header) - height);
+        }
+
+        Some((header_row, Rangef::new(top, top + height)))
+    }
+
+    /// The detail panels of the visible [`Table::detail_rows`], spanning the full width of the table.
+    fn details_ui(&mut self, ui: &mut Ui) {
+        if self.table.details.is_empty() || self.table.num_rows == 0 {
+            return;
+        }
+
+        let footer_height: f32 = self.table.footers.iter().map(|f| f.height).sum();
+        let mut body_rect = ui.clip_rect();
+        body_rect.min.y = self.body_top();
+        body_rect.max.y -= footer_height;
+        body_rect.max.x = body_rect
+            .max
+            .x
+            .at_most(self.col_x.last() - self.scroll_offset.x);
+
+        let viewport = body_rect.translate(self.scroll_offset);
+        let first_row = self.get_row_nr_at_y_offset(viewport.top() - self.body_top());
+        let last_row = self
+            .get_row_nr_at_y_offset(viewport.bottom() - self.body_top())
+            .at_most(self.table.num_rows - 1);
+
+        // Don't cover the section header stuck to the top:
+        if let Some((_, y_range)) = self.sticky_section_header(viewport, &(first_row..last_row + 1))
+        {
+            body_rect.min.y = body_rect.min.y.max(y_range.max - self.scroll_offset.y);
+        }
+
+        let details = &self.table.details;
+        let start = details.partition_point(|(row_nr, _, _)| *row_nr < first_row);
+        let end = details.partition_point(|(row_nr, _, _)| *row_nr <= last_row);
+        for index in start..end {
+            let (row_nr, height, _) = self.table.details[index];
+            if self.table.is_hidden_in_body(row_nr) {
+                continue;
+            }
+
+            let top = self.body_top() + self.get_row_top_offset(row_nr) + self.row_height(row_nr)
+                - self.scroll_offset.y;
+            let detail_rect = Rect::from_x_y_ranges(body_rect.x_range(), top..=top + height);
+
+            let mut detail_ui = ui.new_child(
+                UiBuilder::new()
+                    .max_rect(detail_rect)
+                    .id_salt(("detail", self.table.row_id(self.table_delegate, row_nr))),
+            );
+            detail_ui.shrink_clip_rect(body_rect);
+            detail_ui
+                .painter()
+                .rect_filled(detail_rect, 0.0, detail_ui.visuals().panel_fill);
+            self.table_delegate.row_detail_ui(&mut detail_ui, row_nr);
+        }
+    }
+
+    /// The visible range of display positions (not column numbers!) in the given viewport.
+    fn visible_col_range(&self, viewport: Rect) -> Range<usize> {
+        if self.display_columns.is_empty() || viewport.left() == viewport.right() {
+            0..0
+        } else if self.do_full_sizing_pass {
+            // We do the UI for all columns during a sizing pass, so we can auto-size ALL columns
+            0..self.display_columns.len()
+        } else {
+            // Only paint the visible columns:
+            let col_idx_at = |x: f32| -> usize {
+                self.col_x
+                    .partition_point(|&col_x| col_x < x)
+                    .saturating_sub(1)
+                    .at_most(self.display_columns.len() - 1)
+            };
+
+            col_idx_at(viewport.min.x)..col_idx_at(viewport.max.x) + 1
+        }
+    }
+
+    /// The pinned rows, below the header.
+    fn pinned_rows_ui(&mut self, ui: &mut Ui, scroll_offset: Vec2) {
+        let viewport = ui.clip_rect().translate(scroll_offset);
+        let col_range = self.visible_col_range(viewport);
+
+        for i in 0..self.table.pinned_rows.len() {
+            let (row_nr, _) = self.table.pinned_rows[i];
+            let y_range = Rangef::new(self.pinned_row_y[i], self.pinned_row_y[i + 1]);
+            self.data_row_ui(
+                ui,
+                row_nr,
+                y_range,
+                col_range.clone(),
+                scroll_offset,
+                RowPlacement::Pinned,
+            );
+        }
+
+        self.keep_editor_alive(ui, scroll_offset, RowPlacement::Pinned);
+    }
+
+    /// One row of data.
+    ///
+    /// `y_range` is before scrolling.
+    fn data_row_ui(
+        &mut self,
+        ui: &mut Ui,
+        row_nr: u64,
+        y_range: Rangef,
+        col_range: Range<usize>,
+        scroll_offset: Vec2,
+        placement: RowPlacement,
+    ) {
+        let row_x_range = self.col_x[0]..=self.col_x[self.col_x.len() - 1];
+        let row_rect = Rect::from_x_y_ranges(row_x_range, y_range).translate(-scroll_offset);
+
+        let mut ui_builder = UiBuilder::new()
+            .max_rect(row_rect)
+            .id_salt((
+                "row",
+                self.table.row_id(self.table_delegate, row_nr),
+                placement,
+            ))
+            .layout(egui::Layout::left_to_right(egui::Align::Center));
+        let has_clickable_rows = self.table.has_clickable_rows();
+        if has_clickable_rows {
+            ui_builder = ui_builder.sense(if self.table.selection_mode == SelectionMode::Cells {
+                Sense::click_and_drag()
+            } else {
+                Sense::click()
+            });
+        }
+        let mut row_ui = ui.new_child(ui_builder);
+        row_ui.set_min_size(row_rect.size());
+
+        if placement == RowPlacement::StickySection {
+            // Hide the rows scrolled in under it:
+            row_ui
+                .painter()
+                .rect_filled(row_rect, 0.0, row_ui.visuals().panel_fill);
+        }
+
+        if has_clickable_rows {
+            self.row_selection_ui(&row_ui, row_nr, row_rect, scroll_offset, placement);
+        }
+
+        self.table_delegate.row_ui(&mut row_ui, row_nr);
+
+        let is_in_body = placement == RowPlacement::Body;
+        let clip_top = ui.clip_rect().top() + scroll_offset.y;
+
+        let mut col_pos = col_range.start;
+        while col_pos < col_range.end {
+            let row_span = if is_in_body {
+                self.cell_row_span(row_nr, self.display_columns[col_pos])
+            } else {
+                row_nr..row_nr + 1
+            };
+            let mut col_pos_range = self.cell_col_pos_range(row_span.start, col_pos);
+            if col_range.start < col_pos {
+                // Only the first cell may start before the visible columns.
+                // This also protects against overlapping spans.
+                col_pos_range.start = col_pos;
+            }
+            col_pos = col_pos_range.end;
+
+            if 1 < row_span.end - row_span.start
+                && self.first_shown_row(row_span.clone(), clip_top) != Some(row_nr)
+            {
+                continue; // Shown with another row of the span
+            }
+
+            let y_range = if row_span == (row_nr..row_nr + 1) {
+                y_range
+            } else {
+                let last_row = row_span.end - 1;
+                let top = self.body_top() + self.get_row_top_offset(row_span.start);
+                let last_row_top = self.body_top() + self.get_row_top_offset(last_row);
+                Rangef::new(top, last_row_top + self.row_height(last_row))
+            };
+            self.body_cell_ui(
+                &mut row_ui,
+                row_span,
+                col_pos_range,
+                y_range,
+                scroll_offset,
+                placement,
+            );
+        }
+    }
+
+    /// The visual rows covered by the body cell at the given row and column.
+    ///
+    /// See [`TableDelegate::cell_row_span`].
+    fn cell_row_span(&self, row_nr: u64, col_nr: usize) -> Range<u64> {
+        let span = self.table_delegate.cell_row_span(row_nr, col_nr);
+        let start = span.start.at_most(row_nr);
+        let end = span.end.at_least(row_nr + 1).at_most(self.table.num_rows);
+        start..end
+    }
+
+    /// The row a cell spanning the given rows is shown with.
+    ///
+    /// This is the first of them that is shown in the body, and not scrolled out of view above `clip_top`.
+    fn first_shown_row(&self, row_span: Range<u64>, clip_top: f32) -> Option<u64> {
+        let first_in_view = partition_point(row_span.start..=row_span.end - 1, |row_nr| {
+            clip_top < self.body_top() + self.get_row_top_offset(row_nr + 1)
+        });
+        (first_in_view..row_span.end).find(|&row_nr| !self.table.is_hidden_in_body(row_nr))
+    }
+
+    /// The display positions covered by the body cell at the given display position.
+    ///
+    /// See [`TableDelegate::cell_span`].
+    /// Spans are cut at the sticky columns, so that each side gets its own cell.
+    fn cell_col_pos_range(&self, row_nr: u64, col_pos: usize) -> Range<usize> {
+        let col_nr = self.display_columns[col_pos];
+        let span = self.table_delegate.cell_span(row_nr, col_nr);
+
+        let num_sticky_cols = self.table.num_sticky_cols;
+        let first_sticky_col_right = self.first_sticky_col_right();
+        let section = if col_pos < num_sticky_cols {
+            0..num_sticky_cols
+        } else if col_pos < first_sticky_col_right {
+            num_sticky_cols..first_sticky_col_right
+        } else {
+            first_sticky_col_right..self.display_columns.len()
+        };
+
+        let in_span = |col_pos: usize| span.contains(&self.display_columns[col_pos]);
+        let mut start = col_pos;
+        while section.start < start && in_span(start - 1) {
+            start -= 1;
+        }
+        let mut end = col_pos + 1;
+        while end < section.end && in_span(end) {
+            end += 1;
+        }
+        start..end
+    }
+
+    /// One body cell, spanning the given rows and display positions.
+    fn body_cell_ui(
+        &mut self,
+        row_ui: &mut Ui,
+        row_range: Range<u64>,
+        col_pos_range: Range<usize>,
+        y_range: Rangef,
+        scroll_offset: Vec2,
+        placement: RowPlacement,
+    ) {
+        let row_nr = row_range.start;
+        let col_pos = col_pos_range.start;
+        let end = col_pos_range.end;
+        let col_nr = self.display_columns[col_pos];
+        let last_column = &self.table.columns[self.display_columns[end - 1]];
+        let auto_size_this_frame = last_column.auto_size_this_frame;
+        let mut cell_rect = Rect::from_x_y_ranges(self.col_x[col_pos]..=self.col_x[end], y_range)
+            .translate(-scroll_offset);
+        let clip_rect = cell_rect;
+        if auto_size_this_frame {
+            // Note: we shrink the cell rect when auto-sizing, but not the clip rect! This is to avoid flicker.
+            cell_rect.max.x = cell_rect.min.x
+                + self.display_columns[col_pos..end]
+                    .iter()
+                    .map(|&col_nr| self.table.columns[col_nr].range.min)
+                    .sum::<f32>();
+        }
+
+        let source_row = self.table.source_row(row_nr);
+        let row_id = self.table_delegate.row_id(source_row);
+        let is_editing = !self.is_editor_shown
+            && self.state.editing.as_ref().is_some_and(|edit| {
+                edit.placement == placement
+                    && self.region_columns.contains(&edit.cell.col_pos)
+                    && row_range.contains(&edit.cell.row_nr)
+                    && col_pos_range.contains(&edit.cell.col_pos)
+            });
+        let mut ui_builder = UiBuilder::new()
+            .max_rect(cell_rect)
+            .layout(egui::Layout::left_to_right(egui::Align::Center));
+        ui_builder = if is_editing {
+            // The editor keeps its state when it is shown from elsewhere, see `keep_editor_alive`:
+            ui_builder.id(self.id.with(("editor", row_id, col_nr)))
+        } else {
+            ui_builder.id_salt((row_id, col_nr, placement))
+        };
+        if auto_size_this_frame {
+            ui_builder = ui_builder.sizing_pass();
+        }
+        let mut cell_ui = row_ui.new_child(ui_builder);
+        cell_ui.shrink_clip_rect(clip_rect);
+
+        let cell = CellCoord { row_nr, col_pos };
+        let is_focused = self.state.focus.is_some_and(|focus| {
+            row_range.contains(&focus.row_nr) && col_pos_range.contains(&focus.col_pos)
+        });
+        if self.state.selection.is_cell_selected(cell) {
+            cell_ui
+                .painter()
+                .rect_filled(clip_rect, 0.0, cell_ui.visuals().selection.bg_fill);
+        }
+
+        if self.table.tree_column == Some(col_nr) {
+            let node = self.table_delegate.tree_node(source_row);
+            let is_expanded = self.state.is_row_expanded(row_id);
+            if self
+                .table_delegate
+                .tree_disclosure_ui(&mut cell_ui, node, is_expanded)
+            {
+                self.state.set_row_expanded(row_id, !is_expanded);
+                cell_ui
+                    .ctx()
+                    .request_discard("egui_table tree row expanded or collapsed");
+            }
+        }
+
+        let cell_info = CellInfo {
+            col_nr,
+            col_pos,
+            col_pos_range: col_pos_range.clone(),
+            row_nr,
+            row_range: row_range.clone(),
+            is_pinned: placement == RowPlacement::Pinned,
+            is_sticky_section_header: placement == RowPlacement::StickySection,
+            source_row,
+            is_focused,
+            table_id: self.id,
+        };
+        if is_editing {
+            self.is_editor_shown = true;
+            self.cell_editor_ui(&mut cell_ui, &cell_info);
+        } else {
+            self.table_delegate.cell_ui(&mut cell_ui, &cell_info);
+        }
+
+        if is_focused && self.has_focus {
+            cell_ui.painter().rect_stroke(
+                clip_rect,
+                0.0,
+                cell_ui.visuals().selection.stroke,
+                egui::StrokeKind::Inside,
+            );
+        }
+
+        if self.table.auto_row_heights
+            && placement == RowPlacement::Body
+            && row_range.end == row_nr + 1
+        {
+            let height = self.measured_row_heights.entry(row_nr).or_default();
+            *height = height.max(cell_ui.min_size().y);
+        }
+
+        // Whatever doesn't fit in the other columns of a span needs to fit in the last one:
+        let width_of_others = self.col_x[end - 1] - self.col_x[col_pos];
+        let width = &mut self.max_column_widths[self.display_columns[end - 1]];
+        *width = width.max(cell_ui.min_size().x - width_of_others);
+    }
+
+    /// The editor of [`TableState::editing_cell`], see [`TableDelegate::cell_editor_ui`].
+    ///
+    /// Commits or cancels the edit when the user is done.
+    fn cell_editor_ui(&mut self, ui: &mut Ui, cell: &CellInfo) {
+        let Some(edit) = &mut self.state.editing else {
+            return;
+        };
+        let placement = edit.placement;
+        let response = self
+            .table_delegate
+            .cell_editor_ui(ui, cell, &mut edit.value);
+        let had_focus = response.has_focus() || response.lost_focus();
+        if std::mem::take(&mut edit.request_focus) {
+            response.request_focus();
+        }
+        if response.has_focus() {
+            // We handle Tab and Escape, instead of egui moving the keyboard focus away:
+            ui.memory_mut(|mem| {
+                mem.set_focus_lock_filter(
+                    response.id,
+                    egui::EventFilter {
+                        tab: true,
+                        horizontal_arrows: true,
+                        vertical_arrows: true,
+                        escape: true,
+                    },
+                );
+            });
+        }
+
+        if let Some(error) = &edit.error {
+            ui.painter().rect_stroke(
+                ui.max_rect(),
+                0.0,
+                (1.0, ui.visuals().error_fg_color),
+                egui::StrokeKind::Inside,
+            );
+            if ui.is_rect_visible(response.rect) {
+                response.show_tooltip_text(error.as_str());
+            }
+        }
+
+        if !had_focus {
+            return;
+        }
+        let (enter, escape, tab, shift) = ui.input(|i| {
+            (
+                i.key_pressed(Key::Enter),
+                i.key_pressed(Key::Escape),
+                i.key_pressed(Key::Tab),
+                i.modifiers.shift,
+            )
+        });
+        if escape {
+            self.state.editing = None;
+            ui.memory_mut(|mem| mem.request_focus(self.id));
+            return;
+        }
+        if !enter && !tab && !response.lost_focus() {
+            return;
+        }
+
+        if let Err(error) = self.table_delegate.commit_edit(cell, edit.value.clone()) {
+            // Keep editing:
+            edit.error = Some(error);
+            // Let the user click away, but not Tab or Enter away:
+            edit.request_focus = enter || tab;
+            return;
+        }
+        self.state.editing = None;
+        self.is_editor_shown = false; // The next edit may be shown this frame
+        if enter || tab {
+            ui.memory_mut(|mem| mem.request_focus(self.id));
+        }
+
+        let next_col_pos = if shift {
+            cell.col_pos_range.start.checked_sub(1)
+        } else {
+            Some(cell.col_pos_range.end).filter(|&col_pos| col_pos < self.display_columns.len())
+        };
+        if tab && let Some(col_pos) = next_col_pos {
+            let next = CellCoord {
+                row_nr: cell.row_nr,
+                col_pos,
+            };
+            self.state.focus = Some(next);
+            let old_selection = self.state.selection.clone();
+            self.state
+                .selection
+                .on_focus_move(self.table.selection_mode, next, Modifiers::NONE);
+            *self.selection_changed |= self.state.selection != old_selection;
+            self.table.start_edit(
+                self.state,
+                self.table_delegate,
+                self.display_columns,
+                next,
+                placement,
+                None,
+            );
+        }
+    }
+
+    /// Show the editor even when its cell is out of view, so that it keeps the keyboard focus.
+    ///
+    /// Call this after showing the rows of the region.
+    fn keep_editor_alive(&mut self, ui: &mut Ui, scroll_offset: Vec2, placement: RowPlacement) {
+        let Some(edit) = &self.state.editing else {
+            return;
+        };
+        let CellCoord { row_nr, col_pos } = edit.cell;
+        if self.is_editor_shown
+            || edit.placement != placement
+            || !self.region_columns.contains(&col_pos)
+        {
+            return;
+        }
+
+        let y_range = if placement == RowPlacement::Pinned {
+            let Some(index) = self
+                .table
+                .pinned_rows
+                .iter()
+                .position(|(pinned, _)| *pinned == row_nr)
+            else {
+                return;
+            };
+            Rangef::new(self.pinned_row_y[index], self.pinned_row_y[index + 1])
+        } else {
+            let top = self.body_top() + self.get_row_top_offset(row_nr);
+            Rangef::new(top, top + self.row_height(row_nr))
+        };
+        let col_pos_range = self.cell_col_pos_range(row_nr, col_pos);
+        self.body_cell_ui(
+            ui,
+            row_nr..row_nr + 1,
+            col_pos_range,
+            y_range,
+            scroll_offset,
+            placement,
+        );
+    }
+
+    fn region_ui(&mut self, ui: &mut Ui, scroll_offset: Vec2, do_prefetch: bool) {
+        // Used to find the visible range of columns and rows:
+        let viewport = ui.clip_rect().translate(scroll_offset);
+
+        // The range of display positions (not column numbers!):
+        let col_range = self.visible_col_range(viewport);
+
+        let row_range = if self.table.num_rows == 0 || viewport.top() == viewport.bottom() {
+            0..0
+        } else {
+            // Only paint the visible rows:
+            let row_idx_at = |y: f32| -> u64 {
+                let row_nr = self.get_row_nr_at_y_offset(y - self.body_top());
+                row_nr.at_most(self.table.num_rows.saturating_sub(1))
+            };
+
+            let margin = if do_prefetch {
+                1.0 // Handle possible rounding errors in the syncing of the scroll offsets
+            } else {
+                0.0
+            };
+
+            row_idx_at(viewport.min.y - margin)..row_idx_at(viewport.max.y + margin) + 1
+        };
+
+        let sticky_section_header = self.sticky_section_header(viewport, &row_range);
+
+        if do_prefetch {
+            self.table_delegate.prepare(&PrefetchInfo {
+                num_sticky_columns: self.table.num_sticky_cols,
+                num_sticky_columns_right: self.table.num_sticky_cols_right,
+                visible_columns: col_range.clone(),
+                visible_rows: row_range.clone(),
+                pinned_rows: self
+                    .table
+                    .pinned_rows
+                    .iter()
+                    .map(|(row_nr, _)| *row_nr)
+                    .collect(),
+                sticky_section_header: sticky_section_header.map(|(row_nr, _)| row_nr),
+                editing_cell: self.state.editing_cell(),
+                column_order: self.display_columns.to_vec(),
+                table_id: self.id,
+            });
+            self.has_prefetched = true;
+            self.state.visible_rows = row_range.clone();
+        } else {
+            debug_assert!(
+                self.has_prefetched,
+                "SplitScroll delegate methods called in unexpected order"
+            );
+        }
+
+        for row_nr in row_range {
+            if self.table.is_hidden_in_body(row_nr) {
+                continue;
+            }
+            let top = self.body_top() + self.get_row_top_offset(row_nr);
+            let y_range = Rangef::new(top, top + self.row_height(row_nr));
+            self.data_row_ui(
+                ui,
+                row_nr,
+                y_range,
+                col_range.clone(),
+                scroll_offset,
+                RowPlacement::Body,
+            );
+        }
+
+        // Painted last, on top of the rows scrolled in under it:
+        if let Some((row_nr, y_range)) = sticky_section_header {
+            self.data_row_ui(
+                ui,
+                row_nr,
+                y_range,
+                col_range.clone(),
+                scroll_offset,
+                RowPlacement::StickySection,
+            );
+        }
+
+        self.keep_editor_alive(ui, scroll_offset, RowPlacement::Body);
+
+        // Save column lines for later interaction:
+        for col_pos in col_range.clone() {
+            let column = &self.table.columns[self.display_columns[col_pos]];
+            let line_x = self.col_x[col_pos + 1] - scroll_offset.x;
+            // Don't paint lines on top of the sticky columns on the right:
+            let is_line_visible =
+                self.do_full_sizing_pass || ui.clip_rect().x_range().contains(line_x);
+            if column.resizable && is_line_visible {
+                update(
+                    &mut self.visible_column_lines,
+                    col_pos,
+                    ColumnResizer {
+                        scroll_offset,
+                        top: *self.header_row_y.last(),
+                    },
+                );
+            }
+        }
+    }
+}
+
+impl SplitScrollDelegate for TableSplitScrollDelegate<'_> {
+    // First to be called
+    fn right_bottom_ui(&mut self, ui: &mut Ui) {
+        if self.table.scroll_to_columns.is_some() || self.table.scroll_to_rows.is_some() {
+            let mut target_rect = ui.clip_rect(); // no scrolling
+            let mut target_align = None;
+
+            if let Some((column_range, align)) = &self.table.scroll_to_columns {
+                // Use the first scrollable column as the base, so that offsets start
+                // at 0 for the first non-sticky column — mirroring how row_top_offset
+                // starts at 0 for the first data row.
+                let scrollable_col_x_base = self.col_x[self.table.num_sticky_cols];
+                let x_from_column_nr = |col_nr: usize| -> f32 {
+                    ui.min_rect().left() + (self.col_x[col_nr] - scrollable_col_x_base)
+                };
+
+                let sticky_width = scrollable_col_x_base - self.col_x.first();
+                let sticky_right_width =
+                    self.col_x.last() - self.col_x[self.first_sticky_col_right()];
+
+                // Subtract sticky_width from the left of the target rect so that when
+                // scroll_to_rect aligns the left of the target to the viewport left, the
+                // actual column lands just right of the sticky columns (not behind them).
+                // Likewise on the right, so it doesn't land behind the sticky columns there.
+                target_rect.min.x = x_from_column_nr(*column_range.start()) - sticky_width;
+                target_rect.max.x = x_from_column_nr(*column_range.end() + 1) + sticky_right_width;
+                target_align = target_align.or(*align);
+            }
+
+            if let Some((row_range, align)) = &self.table.scroll_to_rows {
+                let y_from_row_nr =
+                    |row_nr: u64| -> f32 { ui.min_rect().top() + self.get_row_top_offset(row_nr) };
+
+                let sticky_height = self.body_top() - self.header_row_y.first();
+                let footer_height: f32 = self.table.footers.iter().map(|f| f.height).sum();
+
+                // Subtract sticky_height from the top of the target rect so that when
+                // scroll_to_rect aligns the top of the target to the viewport top, the
+                // actual row lands just below the sticky header (not behind it).
+                // Likewise at the bottom, so it doesn't land behind the footer.
+                target_rect.min.y = y_from_row_nr(*row_range.start()) - sticky_height;
+                target_rect.max.y = y_from_row_nr(*row_range.end() + 1) + footer_height;
+                target_align = target_align.or(*align);
+            }
+
+            ui.scroll_to_rect(target_rect, target_align);
+        }
+
+        let scroll_offset = ui.clip_rect().min - ui.min_rect().min;
+        self.scroll_offset = scroll_offset;
+        self.state.scroll_offset = scroll_offset;
+        self.drag_selection_ui(ui, scroll_offset);
+        self.region_columns = self.table.num_sticky_cols..self.first_sticky_col_right();
+        self.region_ui(ui, scroll_offset, true);
+    }
+
+    fn left_top_ui(&mut self, ui: &mut Ui) {
+        self.header_ui(ui, Vec2::ZERO);
+        self.region_columns = 0..self.table.num_sticky_cols;
+        self.filter_row_ui(ui, Vec2::ZERO);
+        self.pinned_rows_ui(ui, Vec2::ZERO);
+    }
+
+    fn right_top_ui(&mut self, ui: &mut Ui) {
+        let scroll_offset = vec2(ui.clip_rect().min.x - ui.min_rect().min.x, 0.0);
+        self.header_ui(ui, scroll_offset);
+        self.region_columns = self.table.num_sticky_cols..self.first_sticky_col_right();
+        self.filter_row_ui(ui, scroll_offset);
+        self.pinned_rows_ui(ui, scroll_offset);
+    }
+
+    fn left_bottom_ui(&mut self, ui: &mut Ui) {
+        self.region_columns = 0..self.table.num_sticky_cols;
+        self.region_ui(
+            ui,
+            vec2(0.0, ui.clip_rect().min.y - ui.min_rect().min.y),
+            false,
+        );
+    }
+
+    fn left_footer_ui(&mut self, ui: &mut Ui) {
+        self.region_columns = 0..self.table.num_sticky_cols;
+        self.footer_ui(ui, 0.0);
+    }
+
+    fn right_footer_ui(&mut self, ui: &mut Ui) {
+        self.region_columns = self.table.num_sticky_cols..self.first_sticky_col_right();
+        self.footer_ui(ui, ui.clip_rect().min.x - ui.min_rect().min.x);
+    }
+
+    fn fixed_right_footer_ui(&mut self, ui: &mut Ui) {
+        self.region_columns = self.first_sticky_col_right()..self.display_columns.len();
+        self.footer_ui(ui, self.sticky_right_offset());
+    }
+
+    fn fixed_right_top_ui(&mut self, ui: &mut Ui) {
+        let scroll_offset = vec2(self.sticky_right_offset(), 0.0);
+        self.header_ui(ui, scroll_offset);
+        self.region_columns = self.first_sticky_col_right()..self.display_columns.len();
+        self.filter_row_ui(ui, scroll_offset);
+        self.pinned_rows_ui(ui, scroll_offset);
+    }
+
+    fn fixed_right_bottom_ui(&mut self, ui: &mut Ui) {
+        self.region_columns = self.first_sticky_col_right()..self.display_columns.len();
+        self.region_ui(
+            ui,
+            vec2(
+                self.sticky_right_offset(),
+                ui.clip_rect().min.y - ui.min_rect().min.y,
+            ),
+            false,
+        );
+    }
+
+    fn finish(&mut self, ui: &mut Ui) {
+        // Paint column resize lines
+
+        for (col_pos, ColumnResizer { scroll_offset, top }) in &self.visible_column_lines {
+            let col_pos = *col_pos;
+            let Some(&col_nr) = self.display_columns.get(col_pos) else {
+                continue;
+            };
+            let Some(column) = self.table.columns.get(col_nr) else {
+                continue;
+            };
+            if !column.resizable {
+                continue;
+            }
+
+            let column_id = column.id_for(col_nr);
+            let used_width = column.range.clamp(self.max_column_widths[col_nr]);
+
+            let column_width = self
+                .state
+                .col_widths
+                .entry(column_id)
+                .or_insert(column.current);
+
+            let layout_width = *column_width; // Width used when computing col_x
+
+            if ui.is_sizing_pass() || column.auto_size_this_frame {
+                // Shrink to fit the widest element in the column:
+                *column_width = used_width;
+            } else {
+                // Grow to fit the widest element in the column:
+                *column_width = column_width.max(used_width);
+            }
+
+            let column_resize_id = self.id.with(column.id_for(col_nr)).with("resize");
+
+            // Right side of the column, adjusted for any width change since layout:
+            let mut x = self.col_x[col_pos + 1] - scroll_offset.x + (*column_width - layout_width);
+            let yrange = Rangef::new(*top, ui.clip_rect().bottom());
+            let line_rect = egui::Rect::from_x_y_ranges(x..=x, yrange)
+                .expand(ui.style().interaction.resize_grab_radius_side);
+
+            let resize_response =
+                ui.interact(line_rect, column_resize_id, egui::Sense::click_and_drag());
+
+            if resize_response.dragged()
+                && let Some(pointer) = ui.ctx().pointer_latest_pos()
+            {
+                // Drag-to-resize.
+                // TODO: use `ui.intrinsic_size` (once it exist) to prevent
+                // resizing below what the content can fit within.
+                let new_width = *column_width + pointer.x - x;
+                let new_width = column.range.clamp(new_width);
+                x += new_width - *column_width;
+                *column_width = new_width;
+            }
+
+            let dragging_something_else =
+                ui.input(|i| i.pointer.any_down() || i.pointer.any_pressed());
+            let resize_hover = resize_response.hovered() && !dragging_something_else;
+
+            if resize_hover || resize_response.dragged() {
+                ui.ctx().set_cursor_icon(egui::CursorIcon::ResizeColumn);
+            }
+
+            let stroke = if resize_response.dragged() {
+                ui.style().visuals.widgets.active.bg_stroke
+            } else if resize_hover {
+                ui.style().visuals.widgets.hovered.bg_stroke
+            } else {
+                // ui.visuals().widgets.inactive.bg_stroke
+                ui.visuals().widgets.noninteractive.bg_stroke
+            };
+
+            ui.painter().vline(x, yrange, stroke);
+        }
+
+        // On top of the column lines, since they ignore the columns:
+        self.details_ui(ui);
+
+        // Show where a dragged column would be dropped:
+        if let Some(ColumnDrag {
+            drop_pos,
+            is_valid: true,
+        }) = self.column_drag
+        {
+            let x = if drop_pos <= self.table.num_sticky_cols {
+                self.col_x[drop_pos]
+            } else if 0 < self.table.num_sticky_cols_right
+                && self.first_sticky_col_right() <= drop_pos
+            {
+                self.col_x[drop_pos] - self.sticky_right_offset()
+            } else {
+                self.col_x[drop_pos] - self.scroll_offset.x
+            };
+            let yrange = Rangef::new(*self.header_row_y.first(), ui.clip_rect().bottom());
+            let stroke = egui::Stroke::new(2.0, ui.visuals().selection.stroke.color);
+            ui.painter().vline(x, yrange, stroke);
+        }
+    }
+}
+
+/// Returns the index of the first element that returns `true` using binary search.
+fn partition_point(range: RangeInclusive<u64>, second_partition: impl Fn(u64) -> bool) -> u64 {
+    let mut min = *range.start();
+    let mut max = *range.end();
+
+    debug_assert!(min < max, "Bad call to partition_point");
+
+    while min < max {
+        let mid = min + (max - min) / 2;
+
+        if second_partition(mid) {
+            max = mid;
+        } else {
+            min = mid + 1;
+        }
+    }
+
+    min
+}
 
-                let num_columns = self.columns.len();
+#[cfg(test)]
+mod tests {
+    use std::{cell::Cell, collections::HashMap};
 
-                for (col_nr, column) in self.columns.iter_mut().enumerate() {
-                    if column.resizable {
-                        let column_resize_id = id.with(column.id_for(col_nr)).with("resize");
-                        if let Some(response) = ui.ctx().read_response(column_resize_id)
-                            && response.double_clicked()
-                        {
-                            column.auto_size_this_frame = true;
-                        }
-                    }
-                    if column.auto_size_this_frame {
-                        ui.ctx().request_discard("egui_table column sizing");
-                    }
-                }
+    use crate::table::{MAX_ROWS_TO_COPY, partition_point};
 
-                SplitScroll {
-                    scroll_enabled: Vec2b::new(true, true),
-                    fixed_size: sticky_size,
-                    scroll_outer_size: (ui.available_size() - sticky_size).at_least(Vec2::ZERO),
-                    scroll_content_size: Vec2::new(
-                        self.columns[self.num_sticky_cols..]
-                            .iter()
-                            .map(|c| c.current)
-                            .sum(),
-                        self.get_row_top_offset(ui.ctx(), id, table_delegate, self.num_rows),
-                    ),
-                    stick_to_bottom: self.stick_to_bottom,
-                }
-                .show(
-                    ui,
-                    &mut TableSplitScrollDelegate {
-                        id,
-                        table_delegate,
-                        state: &mut state,
-                        table: &mut self,
-                        col_x,
-                        header_row_y,
-                        max_column_widths: vec![0.0; num_columns],
-                        visible_column_lines: Default::default(),
-                        do_full_sizing_pass,
-                        has_prefetched: false,
-                        egui_ctx: ui.ctx().clone(),
-                    },
-                );
-            })
-            .response;
+    use super::{
+        Arc, BTreeMap, CellCoord, CellInfo, CellSelection, Column, ColumnFilter, FilterState,
+        FilterValue, FooterCellInfo, FooterRow, HeaderCellInfo, HeaderRow, PrefetchInfo, Range,
+        RowHeights, RowMapping, Table, TableDelegate, TableState, TextFormat, TreeNode,
+    };
 
-        state.store(ui.ctx(), id);
-        response
+    struct Delegate;
+
+    impl TableDelegate for Delegate {
+        fn header_cell_ui(&mut self, _ui: &mut egui::Ui, _cell: &HeaderCellInfo) {}
+
+        fn cell_ui(&mut self, _ui: &mut egui::Ui, _cell: &CellInfo) {}
     }
-}
 
-#[derive(Clone, Copy, Debug)]
-struct ColumnResizer {
-    scroll_offset: Vec2,
+    /// The delegate of the tests that show a table, recording what the table asks of it.
+    ///
+    /// Each cell holds a number ending in its column, e.g. `21` for row 2, column 1.
+    #[derive(Default)]
+    struct TestDelegate {
+        /// The cells shown in the last frame, and where.
+        cells: Vec<(CellInfo, egui::Rect)>,
 
-    top: f32,
-}
+        /// The footer cells shown in the last frame.
+        footer_cells: Vec<FooterCellInfo>,
 
-fn update(map: &mut BTreeMap<usize, ColumnResizer>, key: usize, value: ColumnResizer) {
-    match map.entry(key) {
-        Entry::Vacant(entry) => {
-            entry.insert(value);
+        /// The [`TableDelegate::prepare`] calls of the last frame.
+        prepared: Vec<PrefetchInfo>,
+
+        /// The [`TableDelegate::on_filters_changed`] calls of the last frame.
+        filter_changes: Vec<FilterState>,
+
+        /// The values committed with [`TableDelegate::commit_edit`], by source row and column.
+        edited: BTreeMap<(u64, usize), String>,
+
+        /// The columns merged in every row, see [`TableDelegate::cell_span`].
+        col_span: Option<Range<usize>>,
+
+        /// The rows merged in the first column, see [`TableDelegate::cell_row_span`].
+        row_span: Option<Range<u64>>,
+
+        /// The number of rows in each section, see [`TableDelegate::section_header_row`].
+        section_len: Option<u64>,
+    }
+
+    impl TableDelegate for TestDelegate {
+        fn prepare(&mut self, info: &PrefetchInfo) {
+            self.prepared.push(info.clone());
         }
-        Entry::Occupied(mut entry) => {
-            entry.get_mut().top = entry.get_mut().top.min(value.top);
+
+        fn header_cell_ui(&mut self, _ui: &mut egui::Ui, _cell: &HeaderCellInfo) {}
+
+        fn footer_cell_ui(&mut self, _ui: &mut egui::Ui, cell: &FooterCellInfo) {
+            self.footer_cells.push(cell.clone());
         }
-    }
-}
 
-struct TableSplitScrollDelegate<'a> {
-    id: Id,
-    table_delegate: &'a mut dyn TableDelegate,
-    table: &'a mut Table,
-    state: &'a mut TableState,
+        fn cell_ui(&mut self, ui: &mut egui::Ui, cell: &CellInfo) {
+            self.cells.push((cell.clone(), ui.max_rect()));
+        }
 
-    /// The x coordinate for the start of each column, plus the end of the last column.
-    col_x: Vec1<f32>,
+        fn cell_span(&self, _row_nr: u64, col_nr: usize) -> Range<usize> {
+            match &self.col_span {
+                Some(span) if span.contains(&col_nr) => span.clone(),
+                _ => col_nr..col_nr + 1,
+            }
+        }
 
-    /// The y coordinate for the start of each header row, plus the end of the last header row.
-    header_row_y: Vec1<f32>,
+        fn cell_row_span(&self, row_nr: u64, col_nr: usize) -> Range<u64> {
+            match &self.row_span {
+                Some(span) if col_nr == 0 && span.contains(&row_nr) => span.clone(),
+                _ => row_nr..row_nr + 1,
+            }
+        }
 
-    /// Actual width of the widest element in each column
-    max_column_widths: Vec<f32>,
+        fn section_header_row(&self, row_nr: u64) -> Option<u64> {
+            self.section_len.map(|len| row_nr / len * len)
+        }
 
-    /// Key is column number. The resizer is to the right of the column.
-    visible_column_lines: BTreeMap<usize, ColumnResizer>,
+        fn cell_text(&self, source_row: u64, col_nr: usize) -> Option<String> {
+            let edited = self.edited.get(&(source_row, col_nr)).cloned();
+            Some(edited.unwrap_or_else(|| format!("{source_row}{col_nr}")))
+        }
 
-    do_full_sizing_pass: bool,
+        fn commit_edit(&mut self, cell: &CellInfo, value: String) -> Result<(), String> {
+            if value.parse::<i64>().is_err() {
+                return Err(format!("{value:?} is not a number"));
+            }
+            self.edited.insert((cell.source_row, cell.col_nr), value);
+            Ok(())
+        }
 
-    has_prefetched: bool,
+        fn column_filter(&self, col_nr: usize) -> Option<ColumnFilter> {
+            (col_nr == 0).then_some(ColumnFilter::Text)
+        }
 
-    egui_ctx: Context,
-}
+        fn on_filters_changed(&mut self, filters: &FilterState) {
+            self.filter_changes.push(filters.clone());
+        }
+    }
 
-impl TableSplitScrollDelegate<'_> {
-    /// Helper wrapper around [`Table::get_row_top_offset`].
-    fn get_row_top_offset(&self, row_nr: u64) -> f32 {
-        self.table
-            .get_row_top_offset(&self.egui_ctx, self.id, self.table_delegate, row_nr)
+    /// Runs one frame with the given input events, passing the ui of a central panel to `add_contents`.
+    fn run_frame(
+        ctx: &egui::Context,
+        events: Vec<egui::Event>,
+        mut add_contents: impl FnMut(&mut egui::Ui),
+    ) {
+        let input = egui::RawInput {
+            events,
+            ..Default::default()
+        };
+        let _output = ctx.run(input, |ctx| {
+            egui::CentralPanel::default().show(ctx, &mut add_contents);
+        });
     }
 
-    /// Helper wrapper around [`Table::get_row_nr_at_y_offset`].
-    fn get_row_nr_at_y_offset(&self, y_offset: f32) -> u64 {
-        self.table
-            .get_row_nr_at_y_offset(&self.egui_ctx, self.id, self.table_delegate, y_offset)
+    /// Runs one frame showing the table made by `table`.
+    ///
+    /// Returns the id of the table, and its state after the frame.
+    fn show_table(
+        ctx: &egui::Context,
+        events: Vec<egui::Event>,
+        table: impl Fn() -> Table,
+        delegate: &mut TestDelegate,
+    ) -> (egui::Id, TableState) {
+        delegate.cells.clear();
+        delegate.footer_cells.clear();
+        delegate.prepared.clear();
+        delegate.filter_changes.clear();
+
+        let mut id = egui::Id::NULL;
+        run_frame(ctx, events, |ui| {
+            let table = table();
+            id = table.get_id(ui);
+            table.show(ui, delegate);
+        });
+        (id, TableState::load(ctx, id).expect("The table was shown"))
     }
 
-    fn header_ui(&mut self, ui: &mut Ui, scroll_offset: Vec2) {
-        for (row_nr, header_row) in self.table.headers.iter().enumerate() {
-            let groups = if header_row.groups.is_empty() {
-                (0..self.table.columns.len()).map(|i| i..i + 1).collect()
-            } else {
-                header_row.groups.clone()
+    fn key(key: egui::Key) -> egui::Event {
+        egui::Event::Key {
+            key,
+            physical_key: None,
+            pressed: true,
+            repeat: false,
+            modifiers: egui::Modifiers::NONE,
+        }
+    }
+
+    #[test]
+    fn test_clickable_rows() {
+        let ctx = egui::Context::default();
+        let mut delegate = TestDelegate::default();
+        let mut run = |keyboard_navigation: bool, events: Vec<egui::Event>| {
+            let table = || {
+                Table::new()
+                    .id_salt("table")
+                    .num_rows(10)
+                    .columns(vec![Column::new(100.0).resizable(false); 2])
+                    .keyboard_navigation(keyboard_navigation)
             };
+            show_table(&ctx, events, table, &mut delegate)
+        };
+        let (id, _) = run(false, vec![]);
+        let table_rect = ctx.read_response(id).expect("The table was shown").rect;
+
+        // Click row 1, below the header:
+        let pos = table_rect.left_top() + egui::vec2(150.0, 16.0 + 30.0);
+        let click = |pressed| egui::Event::PointerButton {
+            pos,
+            button: egui::PointerButton::Primary,
+            pressed,
+            modifiers: egui::Modifiers::NONE,
+        };
+        let press = vec![egui::Event::PointerMoved(pos), click(true)];
+        run(false, press.clone());
+        let (_, state) = run(false, vec![click(false)]);
+        assert_eq!(state.focus, None, "The rows leave the clicks to the cells");
+        assert!(!ctx.memory(|mem| mem.has_focus(id)));
+
+        run(true, vec![]);
+        run(true, press);
+        let (_, state) = run(true, vec![click(false)]);
+
+        assert_eq!(
+            state.focus,
+            Some(CellCoord {
+                row_nr: 1,
+                col_pos: 1
+            })
+        );
+        assert!(ctx.memory(|mem| mem.has_focus(id)));
 
-            let y_range = Rangef::new(self.header_row_y[row_nr], self.header_row_y[row_nr + 1]);
+        let (_, state) = run(true, vec![key(egui::Key::ArrowDown)]);
+        assert_eq!(state.focus.map(|focus| focus.row_nr), Some(2));
+    }
 
-            for (group_index, col_range) in groups.into_iter().enumerate() {
-                let start = col_range.start;
-                let end = col_range.end;
+    #[test]
+    fn test_hidden_columns() {
+        let ctx = egui::Context::default();
+        let mut delegate = TestDelegate::default();
+        let table = || {
+            Table::new().id_salt("table").num_rows(10).columns(vec![
+                Column::new(100.0)
+                    .resizable(false);
+                4
+            ])
+        };
+        let (id, mut state) = show_table(&ctx, vec![], table, &mut delegate);
+        state.set_column_visible(egui::Id::new(1_usize), false);
+        state.store(&ctx, id);
+        show_table(&ctx, vec![], table, &mut delegate);
+
+        let info = &delegate.prepared[0];
+        assert_eq!(info.column_order, [0, 2, 3]);
+        assert_eq!(info.visible_columns, 0..3);
+
+        // The display position, column and left edge of the cells in row 0:
+        let table_left = ctx
+            .read_response(id)
+            .expect("The table was shown")
+            .rect
+            .left();
+        let mut cells: Vec<(usize, usize, f32)> = (delegate.cells.iter())
+            .filter(|(cell, _)| cell.row_nr == 0)
+            .map(|(cell, rect)| (cell.col_pos, cell.col_nr, rect.left() - table_left))
+            .collect();
+        cells.sort_by_key(|&(col_pos, ..)| col_pos);
+        assert_eq!(cells, [(0, 0, 0.0), (1, 2, 100.0), (2, 3, 200.0)]);
+    }
 
-                let mut header_rect =
-                    Rect::from_x_y_ranges(self.col_x[start]..=self.col_x[end], y_range)
-                        .translate(-scroll_offset);
+    #[test]
+    fn test_sticky_columns_right() {
+        let ctx = egui::Context::default();
+        ctx.style_mut(|style| style.scroll_animation = egui::style::ScrollAnimation::none());
+        let mut delegate = TestDelegate::default();
+        let mut run = |scroll_to_column: Option<usize>| {
+            let table = || {
+                let table = Table::new()
+                    .id_salt("table")
+                    .num_rows(10)
+                    .columns(vec![Column::new(500.0).resizable(false); 30])
+                    .num_sticky_cols(1)
+                    .num_sticky_cols_right(1);
+                match scroll_to_column {
+                    Some(col_nr) => table.scroll_to_column(col_nr, Some(egui::Align::LEFT)),
+                    None => table,
+                }
+            };
+            let (id, _) = show_table(&ctx, vec![], table, &mut delegate);
+            let table_rect = ctx.read_response(id).expect("The table was shown").rect;
+
+            // The left edges of the cells in row 0, by column:
+            let lefts: BTreeMap<usize, f32> = (delegate.cells.iter())
+                .filter(|(cell, _)| cell.row_nr == 0)
+                .map(|(cell, rect)| (cell.col_nr, rect.left() - table_rect.left()))
+                .collect();
+            (
+                table_rect,
+                lefts,
+                delegate.prepared[0].visible_columns.clone(),
+            )
+        };
 
-                if 0 < start
-                    && self.table.columns[start - 1].resizable
-                    && ui.clip_rect().x_range().contains(header_rect.left())
-                {
-                    // The previous column is resizable, so make sure the resize line goes to above this heading:
-                    update(
-                        &mut self.visible_column_lines,
-                        start - 1,
-                        ColumnResizer {
-                            scroll_offset,
-                            top: header_rect.top(),
+        run(None);
+        let (table_rect, lefts, _) = run(None);
+        let right_sticky_left = table_rect.width() - 500.0;
+        assert_eq!((lefts[&0], lefts[&1]), (0.0, 500.0));
+        assert_eq!(lefts[&29], right_sticky_left);
+
+        // Scroll all the way to the right:
+        run(Some(28));
+        run(None);
+        let (_, lefts, visible_columns) = run(None);
+        assert_eq!(visible_columns.end, 29);
+        assert!(!lefts.contains_key(&1), "Scrolled out of view");
+        assert_eq!(lefts[&0], 0.0, "The left sticky column stays");
+        assert_eq!(
+            lefts[&29], right_sticky_left,
+            "The right sticky column stays"
+        );
+        assert_eq!(lefts[&28], right_sticky_left - 500.0);
+    }
+
+    #[test]
+    fn test_footers() {
+        let ctx = egui::Context::default();
+        let mut delegate = TestDelegate::default();
+        let mut run = |num_sticky_cols| {
+            let table = || {
+                Table::new()
+                    .id_salt("table")
+                    .num_rows(10)
+                    .columns(vec![Column::new(100.0).resizable(false); 4])
+                    .num_sticky_cols(num_sticky_cols)
+                    .num_sticky_cols_right(1)
+                    .footers([
+                        FooterRow {
+                            height: 24.0,
+                            groups: vec![0..3, 3..4],
                         },
-                    );
-                }
+                        FooterRow::new(24.0),
+                    ])
+            };
+            show_table(&ctx, vec![], table, &mut delegate);
+            show_table(&ctx, vec![], table, &mut delegate);
+
+            let mut footer_cells: Vec<(usize, Range<usize>)> = (delegate.footer_cells.iter())
+                .map(|cell| (cell.row_nr, cell.col_range.clone()))
+                .collect();
+            footer_cells.sort_by_key(|(row_nr, col_range)| (*row_nr, col_range.start));
+            footer_cells
+        };
 
-                let clip_rect = header_rect;
+        assert_eq!(
+            run(0),
+            [
+                (0, 0..3),
+                (0, 3..4),
+                (1, 0..1),
+                (1, 1..2),
+                (1, 2..3),
+                (1, 3..4),
+            ],
+            "One cell per group, or per column without groups"
+        );
+        assert_eq!(
+            run(1),
+            [
+                (0, 0..3),
+                (0, 0..3),
+                (0, 3..4),
+                (1, 0..1),
+                (1, 1..2),
+                (1, 2..3),
+                (1, 3..4),
+            ],
+            "Shown on both sides of the sticky column"
+        );
+    }
 
-                let last_column = &self.table.columns[end - 1];
-                let auto_size_this_frame = last_column.auto_size_this_frame; // TODO: correct?
+    #[test]
+    fn test_sticky_section_header() {
+        let ctx = egui::Context::default();
+        ctx.style_mut(|style| style.scroll_animation = egui::style::ScrollAnimation::none());
+        let mut delegate = TestDelegate {
+            section_len: Some(10),
+            ..Default::default()
+        };
+        // The stuck section header, and whether it is at the top of the body:
+        let mut run = |scroll_to_row: u64| {
+            let table = || {
+                Table::new()
+                    .id_salt("table")
+                    .num_rows(1_000)
+                    .columns(vec![Column::new(100.0); 2])
+                    .headers([HeaderRow::new(20.0)])
+                    .scroll_to_row(scroll_to_row, Some(egui::Align::TOP))
+            };
+            show_table(&ctx, vec![], table, &mut delegate);
+            show_table(&ctx, vec![], table, &mut delegate);
+            let (id, _) = show_table(&ctx, vec![], table, &mut delegate);
+
+            let table_rect = ctx.read_response(id).expect("The table was shown").rect;
+            (delegate.cells.iter())
+                .find(|(cell, _)| cell.is_sticky_section_header)
+                .map(|(cell, rect)| (cell.row_nr, rect.top() == table_rect.top() + 20.0))
+        };
 
-                if auto_size_this_frame {
-                    // Note: we shrink the cell rect when auto-sizing, but not the clip rect! This is to avoid flicker.
-                    header_rect.max.x = header_rect.min.x
-                        + self.table.columns[start..end]
-                            .iter()
-                            .map(|column| column.range.min)
-                            .sum::<f32>();
-                }
+        assert_eq!(run(0), None, "The section header is in view");
+        assert_eq!(run(15), Some((10, true)));
+        assert_eq!(run(19), Some((10, true)));
+        assert_eq!(run(25), Some((20, true)), "The next section");
+    }
 
-                let mut ui_builder = UiBuilder::new()
-                    .max_rect(header_rect)
-                    .id_salt(("header", row_nr, group_index))
-                    .layout(egui::Layout::left_to_right(egui::Align::Center));
-                if auto_size_this_frame {
-                    ui_builder = ui_builder.sizing_pass();
-                }
-                let mut cell_ui = ui.new_child(ui_builder);
-                cell_ui.shrink_clip_rect(clip_rect);
+    #[test]
+    fn test_follow_moved_columns() {
+        let cell = |row_nr, col_pos| CellCoord { row_nr, col_pos };
+        let mut state = TableState {
+            focus: Some(cell(3, 1)),
+            ..Default::default()
+        };
+        state.selection.cells = Some(CellSelection {
+            anchor: cell(1, 0),
+            focus: cell(2, 2),
+        });
 
-                self.table_delegate.header_cell_ui(
-                    &mut cell_ui,
-                    &HeaderCellInfo {
-                        group_index,
-                        col_range,
-                        row_nr,
-                        table_id: self.id,
-                    },
-                );
+        // Column 0 is dragged to before column 3:
+        state.follow_moved_columns(&[0, 1, 2, 3], &[1, 2, 0, 3]);
+        assert_eq!(state.focus, Some(cell(3, 0)));
+        assert_eq!(
+            state.selection.cells,
+            Some(CellSelection {
+                anchor: cell(1, 2),
+                focus: cell(2, 1),
+            }),
+            "The selected cells stay with their columns"
+        );
+    }
 
-                if start + 1 == end {
-                    // normal single-column group
-                    let col_nr = start;
-                    let column = &self.table.columns[start];
-                    let width = &mut self.max_column_widths[col_nr];
-                    *width = width.max(cell_ui.min_size().x);
+    #[test]
+    fn test_pinned_rows_hidden_in_body() {
+        let ctx = egui::Context::default();
+        let id = egui::Id::new("table");
+        let offset = |table: &Table, row_nr| table.get_row_top_offset(&ctx, id, &Delegate, row_nr);
+
+        let mut table = Table::new().num_rows(10);
+        table.pinned_rows = vec![(5, 20.0), (2, 20.0)];
+        assert_eq!(offset(&table, 6), 120.0);
+        assert!(!table.is_hidden_in_body(2));
+
+        table = table.show_pinned_rows_in_body(false);
+        table.pinned_rows = vec![(5, 20.0), (2, 20.0)];
+        assert_eq!(offset(&table, 2), 40.0);
+        assert_eq!(offset(&table, 3), 40.0, "Row 2 takes up no space");
+        assert_eq!(offset(&table, 6), 80.0);
+        assert_eq!(offset(&table, 10), 160.0);
+        assert!(table.is_hidden_in_body(2));
+        assert!(!table.is_hidden_in_body(3));
+    }
 
-                    // Save column lines for later interaction:
-                    if column.resizable && ui.clip_rect().x_range().contains(header_rect.right()) {
-                        update(
-                            &mut self.visible_column_lines,
-                            col_nr,
-                            ColumnResizer {
-                                scroll_offset,
-                                top: header_rect.top(),
-                            },
-                        );
+    #[test]
+    fn test_tree_rows_are_cached() {
+        struct Tree {
+            num_tree_node_calls: std::cell::Cell<usize>,
+        }
+
+        impl TableDelegate for Tree {
+            fn header_cell_ui(&mut self, _ui: &mut egui::Ui, _cell: &HeaderCellInfo) {}
+
+            fn cell_ui(&mut self, _ui: &mut egui::Ui, _cell: &CellInfo) {}
+
+            fn tree_node(&self, source_row: u64) -> TreeNode {
+                self.num_tree_node_calls
+                    .set(self.num_tree_node_calls.get() + 1);
+                // Row 0 is the parent of rows 1 and 2:
+                if source_row == 0 {
+                    TreeNode {
+                        depth: 0,
+                        num_descendants: 2,
                     }
+                } else {
+                    TreeNode::leaf(1)
                 }
             }
         }
+
+        let ctx = egui::Context::default();
+        let id = egui::Id::new("table");
+        let tree = Tree {
+            num_tree_node_calls: Default::default(),
+        };
+        let mut state = TableState::default();
+        let update = |state: &mut TableState, num_rows| {
+            let mut table = Table::new().num_rows(num_rows).tree_column(0);
+            table.update_rows(&ctx, id, state, &tree);
+            table.num_rows
+        };
+
+        assert_eq!(update(&mut state, 3), 1, "Collapsed");
+        let num_calls = tree.num_tree_node_calls.get();
+        assert_eq!(update(&mut state, 3), 1);
+        assert_eq!(tree.num_tree_node_calls.get(), num_calls, "Cached");
+
+        state.set_row_expanded(tree.row_id(0), true);
+        assert_eq!(update(&mut state, 3), 3);
+        assert_eq!(
+            update(&mut state, 4),
+            4,
+            "Found again when the number of rows changes"
+        );
+
+        state.expanded_rows.clear();
+        assert_eq!(update(&mut state, 4), 4, "Still cached");
+        state.refresh_tree();
+        assert_eq!(update(&mut state, 4), 2);
     }
 
-    fn region_ui(&mut self, ui: &mut Ui, scroll_offset: Vec2, do_prefetch: bool) {
-        // Used to find the visible range of columns and rows:
-        let viewport = ui.clip_rect().translate(scroll_offset);
+    #[test]
+    fn test_row_top_offset() {
+        struct TallRows;
 
-        let col_range = if self.table.columns.is_empty() || viewport.left() == viewport.right() {
-            0..0
-        } else if self.do_full_sizing_pass {
-            // We do the UI for all columns during a sizing pass, so we can auto-size ALL columns
-            0..self.table.columns.len()
-        } else {
-            // Only paint the visible columns:
-            let col_idx_at = |x: f32| -> usize {
-                self.col_x
-                    .partition_point(|&col_x| col_x < x)
-                    .saturating_sub(1)
-                    .at_most(self.table.columns.len() - 1)
-            };
+        impl TableDelegate for TallRows {
+            fn header_cell_ui(&mut self, _ui: &mut egui::Ui, _cell: &HeaderCellInfo) {}
+
+            fn cell_ui(&mut self, _ui: &mut egui::Ui, _cell: &CellInfo) {}
+
+            fn row_top_offset_f64(
+                &self,
+                _ctx: &egui::Context,
+                _table_id: egui::Id,
+                row_nr: u64,
+            ) -> f64 {
+                row_nr as f64 * 30.0
+            }
+        }
+
+        let ctx = egui::Context::default();
+        let id = egui::Id::new("table");
+        let table = Table::new().num_rows(100);
+        assert_eq!(table.get_row_top_offset(&ctx, id, &TallRows, 10), 300.0);
+        assert_eq!(table.get_row_top_offset(&ctx, id, &Delegate, 10), 200.0);
+        assert_eq!(TallRows.row_top_offset(&ctx, id, 10), 300.0);
+
+        let far_down = 1_000_000_001;
+        assert_eq!(
+            Delegate.row_top_offset_f64(&ctx, id, far_down),
+            20_000_000_020.0,
+            "Precise by default"
+        );
+    }
+
+    #[test]
+    fn test_detail_rows() {
+        let ctx = egui::Context::default();
+        let id = egui::Id::new("table");
+        let offset = |table: &Table, row_nr| table.get_row_top_offset(&ctx, id, &Delegate, row_nr);
+
+        // Each detail panel is 80 high, each row 20:
+        let mut table = Table::new().num_rows(10).detail_rows([5, 2, 2, 12]);
+        table.update_rows(&ctx, id, &mut TableState::default(), &Delegate);
+        assert_eq!(offset(&table, 2), 40.0);
+        assert_eq!(offset(&table, 3), 140.0);
+        assert_eq!(offset(&table, 6), 280.0);
+        assert_eq!(offset(&table, 10), 360.0, "Rows out of range are ignored");
+        assert_eq!(table.detail_height(5), 80.0);
+        assert_eq!(table.detail_height(6), 0.0);
+    }
+
+    #[test]
+    fn test_auto_row_heights() {
+        let ctx = egui::Context::default();
+        let id = egui::Id::new("table");
+        let offset = |table: &Table, row_nr| table.get_row_top_offset(&ctx, id, &Delegate, row_nr);
+
+        // Rows that haven't been measured are 20 high:
+        let mut table = Table::new().num_rows(10).auto_row_heights(true);
+        table.row_heights = RowHeights::new(20.0);
+        let mut state = TableState::default();
+        table.update_rows(&ctx, id, &mut state, &Delegate);
+        assert_eq!(offset(&table, 4), 80.0);
+
+        state.scroll_offset.y = 50.0; // Row 2 is at the top
+        let measured = [(0, 30.0), (1, 10.0), (3, 50.0)].into_iter().collect();
+        table.update_row_heights(&ctx, id, &mut state, &Delegate, measured);
+        assert_eq!(
+            state.row_heights.iter().collect::<Vec<_>>(),
+            [(0, 30.0), (3, 50.0)],
+            "No row is shorter than the default"
+        );
+        assert_eq!(state.scroll_correction, 10.0, "Row 0 grew above the view");
+
+        table.row_heights = std::mem::take(&mut state.row_heights);
+        assert_eq!(offset(&table, 3), 70.0);
+        assert_eq!(offset(&table, 4), 120.0);
+
+        // E.g. when a filter hides all rows:
+        let mut table = Table::new().num_rows(0).auto_row_heights(true);
+        table.row_heights = RowHeights::new(20.0);
+        table.row_heights.set_height(3, 50.0);
+        table.update_rows(&ctx, id, &mut state, &Delegate);
+        table.update_row_heights(&ctx, id, &mut state, &Delegate, Default::default());
+        assert_eq!(
+            state.row_heights.iter().collect::<Vec<_>>(),
+            [(3, 50.0)],
+            "Keeps the heights of the empty table"
+        );
+    }
+
+    #[test]
+    fn test_huge_table() {
+        let ctx = egui::Context::default();
+        let id = egui::Id::new("table");
+
+        let mut table = Table::new().num_rows(5_000_000_000);
+        table.update_rows(&ctx, id, &mut TableState::default(), &Delegate);
+        let body_height = table.body_row_top_offset(&ctx, id, &Delegate, table.num_rows);
+        assert_eq!(
+            Table::scroll_origin_around(100_000.0, 50_000.0),
+            0.0,
+            "Short tables never move the scroll window"
+        );
+
+        let row_nr = 4_000_000_000;
+        let top = table.body_row_top_offset(&ctx, id, &Delegate, row_nr);
+        table.scroll_origin = Table::scroll_origin_around(body_height, top);
+        let offset = table.get_row_top_offset(&ctx, id, &Delegate, row_nr);
+        assert_eq!(offset, 500_000.0, "In the middle of the scroll window");
+        assert_eq!(
+            table.get_row_top_offset(&ctx, id, &Delegate, row_nr + 1) - offset,
+            20.0
+        );
+        assert_eq!(
+            table.get_row_nr_at_y_offset(&ctx, id, &Delegate, offset + 19.0),
+            row_nr
+        );
+        assert_eq!(
+            table.get_row_nr_at_y_offset(&ctx, id, &Delegate, offset + 21.0),
+            row_nr + 1
+        );
+    }
+
+    #[test]
+    fn test_scroll_anchor() {
+        /// A log with the newest entry at the top.
+        struct Log {
+            newest_entry: u64,
+            num_entries: u64,
+        }
 
-            col_idx_at(viewport.min.x)..col_idx_at(viewport.max.x) + 1
-        };
+        impl TableDelegate for Log {
+            fn header_cell_ui(&mut self, _ui: &mut egui::Ui, _cell: &HeaderCellInfo) {}
 
-        let row_range = if self.table.num_rows == 0 || viewport.top() == viewport.bottom() {
-            0..0
-        } else {
-            // Only paint the visible rows:
-            let row_idx_at = |y: f32| -> u64 {
-                let row_nr = self.get_row_nr_at_y_offset(y - self.header_row_y.last());
-                row_nr.at_most(self.table.num_rows.saturating_sub(1))
-            };
+            fn cell_ui(&mut self, _ui: &mut egui::Ui, _cell: &CellInfo) {}
 
-            let margin = if do_prefetch {
-                1.0 // Handle possible rounding errors in the syncing of the scroll offsets
-            } else {
-                0.0
-            };
+            fn row_id(&self, row_nr: u64) -> egui::Id {
+                egui::Id::new(self.newest_entry - row_nr)
+            }
+        }
 
-            row_idx_at(viewport.min.y - margin)..row_idx_at(viewport.max.y + margin) + 1
+        let ctx = egui::Context::default();
+        let id = egui::Id::new("table");
+        let mut state = TableState::default();
+        let mut log = Log {
+            newest_entry: 100,
+            num_entries: 100,
+        };
+        let show = |log: &Log, state: &mut TableState| {
+            let mut table = Table::new().num_rows(log.num_entries);
+            let body_height = table.body_row_top_offset(&ctx, id, log, table.num_rows);
+            table.follow_scroll_anchor(&ctx, id, state, log, body_height);
+            state.scroll_offset.y += std::mem::take(&mut state.scroll_correction);
+            state.scroll_anchor = table.scroll_anchor(&ctx, id, state, log);
         };
 
-        if do_prefetch {
-            self.table_delegate.prepare(&PrefetchInfo {
-                num_sticky_columns: self.table.num_sticky_cols,
-                visible_columns: col_range.clone(),
-                visible_rows: row_range.clone(),
-                table_id: self.id,
-            });
-            self.has_prefetched = true;
-        } else {
-            debug_assert!(
-                self.has_prefetched,
-                "SplitScroll delegate methods called in unexpected order"
-            );
-        }
+        state.scroll_offset.y = 210.0; // 10 pixels into row 10
+        show(&log, &mut state);
+        log.newest_entry += 3;
+        log.num_entries += 3;
+        show(&log, &mut state);
+        assert_eq!(state.scroll_offset.y, 270.0, "Follows the three new rows");
+
+        // Entries removed both above and below the view:
+        log.newest_entry -= 3;
+        log.num_entries -= 43;
+        show(&log, &mut state);
+        assert_eq!(state.scroll_offset.y, 210.0);
+
+        state.scroll_offset.y = 0.0;
+        show(&log, &mut state);
+        log.newest_entry += 3;
+        log.num_entries += 3;
+        show(&log, &mut state);
+        assert_eq!(
+            state.scroll_offset.y, 0.0,
+            "Stays at the top, showing the new rows"
+        );
 
-        for row_nr in row_range {
-            let y_range = Rangef::new(
-                self.header_row_y.last() + self.get_row_top_offset(row_nr),
-                self.header_row_y.last() + self.get_row_top_offset(row_nr + 1),
-            );
+        // All rows removed while scrolled down:
+        state.scroll_offset.y = 210.0;
+        show(&log, &mut state);
+        log.num_entries = 0;
+        show(&log, &mut state);
+        assert!(state.scroll_anchor.is_none());
+    }
 
-            let row_x_range = self.col_x[0]..=self.col_x[self.col_x.len() - 1];
-            let row_rect = Rect::from_x_y_ranges(row_x_range, y_range).translate(-scroll_offset);
+    #[test]
+    fn test_row_ids() {
+        let ctx = egui::Context::default();
+        let id = egui::Id::new("table");
+        let mut state = TableState::default();
+        let update = |row_mapping: RowMapping, state: &mut TableState| {
+            let mut table = Table::new().row_mapping(row_mapping);
+            table.update_rows(&ctx, id, state, &Delegate);
+            table.remember_row_ids(state, &Delegate);
+        };
 
-            let mut row_ui = ui.new_child(
-                UiBuilder::new()
-                    .max_rect(row_rect)
-                    .id_salt(("row", row_nr))
-                    .layout(egui::Layout::left_to_right(egui::Align::Center)),
-            );
-            row_ui.set_min_size(row_rect.size());
+        update(RowMapping::identity(10), &mut state);
+        state.selection.select_row(2);
+        state.focus = Some(CellCoord {
+            row_nr: 2,
+            col_pos: 1,
+        });
+        state.pin_row(3);
+        update(RowMapping::identity(10), &mut state);
+
+        let mut reversed = RowMapping::identity(10);
+        reversed.reverse();
+        update(reversed, &mut state);
+        assert!(
+            state.selection.is_row_selected(7),
+            "The selection follows the sort"
+        );
+        assert_eq!(state.selection.anchor, Some(7));
+        assert_eq!(
+            state.focus,
+            Some(CellCoord {
+                row_nr: 7,
+                col_pos: 1
+            })
+        );
+        assert_eq!(state.pinned_rows, [6]);
 
-            self.table_delegate.row_ui(&mut row_ui, row_nr);
+        // Filter out the selected row:
+        update(RowMapping::filtered(10, |row| row != 2), &mut state);
+        assert!(state.selection.is_empty());
+        assert_eq!(state.focus, None);
+        assert_eq!(state.pinned_rows, [2]);
+    }
 
-            for col_nr in col_range.clone() {
-                let column = &self.table.columns[col_nr];
-                let mut cell_rect =
-                    Rect::from_x_y_ranges(self.col_x[col_nr]..=self.col_x[col_nr + 1], y_range)
-                        .translate(-scroll_offset);
-                let clip_rect = cell_rect;
-                if column.auto_size_this_frame {
-                    // Note: we shrink the cell rect when auto-sizing, but not the clip rect! This is to avoid flicker.
-                    cell_rect.max.x = cell_rect.min.x + column.range.min;
-                }
+    #[test]
+    fn test_row_heights_follow_rows() {
+        /// A log with the newest entry at the top, unless reversed.
+        struct Log {
+            newest_entry: u64,
+            num_entries: u64,
+            reversed: bool,
+        }
 
-                let mut ui_builder = UiBuilder::new()
-                    .max_rect(cell_rect)
-                    .id_salt((row_nr, col_nr))
-                    .layout(egui::Layout::left_to_right(egui::Align::Center));
-                if column.auto_size_this_frame {
-                    ui_builder = ui_builder.sizing_pass();
-                }
-                let mut cell_ui = row_ui.new_child(ui_builder);
-                cell_ui.shrink_clip_rect(clip_rect);
+        impl TableDelegate for Log {
+            fn header_cell_ui(&mut self, _ui: &mut egui::Ui, _cell: &HeaderCellInfo) {}
 
-                self.table_delegate.cell_ui(
-                    &mut cell_ui,
-                    &CellInfo {
-                        col_nr,
-                        row_nr,
-                        table_id: self.id,
-                    },
-                );
+            fn cell_ui(&mut self, _ui: &mut egui::Ui, _cell: &CellInfo) {}
 
-                let width = &mut self.max_column_widths[col_nr];
-                *width = width.max(cell_ui.min_size().x);
+            fn row_id(&self, row_nr: u64) -> egui::Id {
+                let age = if self.reversed {
+                    self.num_entries - 1 - row_nr
+                } else {
+                    row_nr
+                };
+                egui::Id::new(self.newest_entry - age)
             }
         }
 
-        // Save column lines for later interaction:
-        for col_nr in col_range.clone() {
-            let column = &self.table.columns[col_nr];
-            if column.resizable {
-                update(
-                    &mut self.visible_column_lines,
-                    col_nr,
-                    ColumnResizer {
-                        scroll_offset,
-                        top: *self.header_row_y.last(),
-                    },
-                );
-            }
-        }
+        let ctx = egui::Context::default();
+        let id = egui::Id::new("table");
+        let mut state = TableState::default();
+        let mut log = Log {
+            newest_entry: 100,
+            num_entries: 10,
+            reversed: false,
+        };
+        let update = |log: &Log, state: &mut TableState| {
+            let mut table = Table::new()
+                .num_rows(log.num_entries)
+                .auto_row_heights(true);
+            table.row_heights = std::mem::take(&mut state.row_heights);
+            table.update_rows(&ctx, id, state, log);
+            table.remember_row_ids(state, log);
+            state.row_heights = table.row_heights;
+        };
+        let heights = |state: &TableState| state.row_heights.iter().collect::<Vec<_>>();
+
+        state.row_heights = RowHeights::new(20.0);
+        state.row_heights.set_height(3, 50.0);
+        state.selection.select_row(5);
+        update(&log, &mut state);
+
+        log.newest_entry += 2;
+        log.num_entries += 2;
+        update(&log, &mut state);
+        assert!(state.selection.is_row_selected(7));
+        assert_eq!(heights(&state), [(5, 50.0)], "Follows the two new rows");
+
+        log.num_entries -= 4;
+        update(&log, &mut state);
+        assert_eq!(heights(&state), [(5, 50.0)], "Rows removed below");
+
+        log.reversed = true;
+        state.refresh_row_ids();
+        update(&log, &mut state);
+        assert!(state.selection.is_row_selected(0));
+        assert_eq!(heights(&state), [], "Measured again after sorting");
     }
-}
-
-impl SplitScrollDelegate for TableSplitScrollDelegate<'_> {
-    // First to be called
-    fn right_bottom_ui(&mut self, ui: &mut Ui) {
-        if self.table.scroll_to_columns.is_some() || self.table.scroll_to_rows.is_some() {
-            let mut target_rect = ui.clip_rect(); // no scrolling
-            let mut target_align = None;
 
-            if let Some((column_range, align)) = &self.table.scroll_to_columns {
-                // Use the first scrollable column as the base, so that offsets start
-                // at 0 for the first non-sticky column — mirroring how row_top_offset
-                // starts at 0 for the first data row.
-                let scrollable_col_x_base = self.col_x[self.table.num_sticky_cols];
-                let x_from_column_nr = |col_nr: usize| -> f32 {
-                    ui.min_rect().left() + (self.col_x[col_nr] - scrollable_col_x_base)
-                };
+    #[test]
+    fn test_row_ids_in_huge_table() {
+        /// Rows that can be reversed, with an index of the ids of some of them.
+        struct Rows {
+            num_rows: u64,
+            reversed: bool,
+            swapped: Option<(u64, u64)>,
+            index: HashMap<egui::Id, u64>,
+            row_id_calls: Cell<u64>,
+        }
 
-                let sticky_width = scrollable_col_x_base - self.col_x.first();
+        impl Rows {
+            fn swap(&self, row: u64) -> u64 {
+                match self.swapped {
+                    Some((a, b)) if row == a => b,
+                    Some((a, b)) if row == b => a,
+                    _ => row,
+                }
+            }
 
-                // Subtract sticky_width from the left of the target rect so that when
-                // scroll_to_rect aligns the left of the target to the viewport left, the
-                // actual column lands just right of the sticky columns (not behind them).
-                target_rect.min.x = x_from_column_nr(*column_range.start()) - sticky_width;
-                target_rect.max.x = x_from_column_nr(*column_range.end() + 1);
-                target_align = target_align.or(*align);
+            fn reverse(&self, row: u64) -> u64 {
+                if self.reversed {
+                    self.num_rows - 1 - row
+                } else {
+                    row
+                }
             }
+        }
 
-            if let Some((row_range, align)) = &self.table.scroll_to_rows {
-                let y_from_row_nr =
-                    |row_nr: u64| -> f32 { ui.min_rect().top() + self.get_row_top_offset(row_nr) };
+        impl TableDelegate for Rows {
+            fn header_cell_ui(&mut self, _ui: &mut egui::Ui, _cell: &HeaderCellInfo) {}
 
-                let sticky_height = self.header_row_y.last() - self.header_row_y.first();
+            fn cell_ui(&mut self, _ui: &mut egui::Ui, _cell: &CellInfo) {}
 
-                // Subtract sticky_height from the top of the target rect so that when
-                // scroll_to_rect aligns the top of the target to the viewport top, the
-                // actual row lands just below the sticky header (not behind it).
-                target_rect.min.y = y_from_row_nr(*row_range.start()) - sticky_height;
-                target_rect.max.y = y_from_row_nr(*row_range.end() + 1);
-                target_align = target_align.or(*align);
+            fn row_id(&self, source_row: u64) -> egui::Id {
+                self.row_id_calls.set(self.row_id_calls.get() + 1);
+                egui::Id::new(self.swap(self.reverse(source_row)))
             }
 
-            ui.scroll_to_rect(target_rect, target_align);
+            fn source_row_of_id(&self, row_id: egui::Id) -> Option<u64> {
+                let key = *self.index.get(&row_id)?;
+                Some(self.reverse(self.swap(key)))
+            }
         }
 
-        let scroll_offset = ui.clip_rect().min - ui.min_rect().min;
-        self.region_ui(ui, scroll_offset, true);
-    }
+        let ctx = egui::Context::default();
+        let id = egui::Id::new("table");
+        let mut state = TableState::default();
+        let mut rows = Rows {
+            num_rows: 10_000_000,
+            reversed: false,
+            swapped: None,
+            index: [(egui::Id::new(5_u64), 5)].into(),
+            row_id_calls: Cell::new(0),
+        };
+        let update = |rows: &Rows, state: &mut TableState| {
+            rows.row_id_calls.set(0);
+            let mut table = Table::new().num_rows(rows.num_rows);
+            table.update_rows(&ctx, id, state, rows);
+            table.remember_row_ids(state, rows);
+            assert!(
+                rows.row_id_calls.get() < 10_000,
+                "Only looks for the tracked rows"
+            );
+        };
 
-    fn left_top_ui(&mut self, ui: &mut Ui) {
-        self.header_ui(ui, Vec2::ZERO);
+        state.selection.select_row(5);
+        state.pin_row(5_000_000);
+        update(&rows, &mut state);
+        update(&rows, &mut state);
+        assert_eq!(rows.row_id_calls.get(), 0, "Nothing changed");
+        state.selection.rows.insert_range(5..8);
+        update(&rows, &mut state);
+        assert_eq!(rows.row_id_calls.get(), 2, "Only the newly selected rows");
+
+        // A row moves a little:
+        rows.swapped = Some((5_000_000, 5_000_500));
+        update(&rows, &mut state);
+        assert_eq!(state.pinned_rows, [5_000_000], "Not looked for by itself");
+        state.refresh_row_ids();
+        update(&rows, &mut state);
+        assert!(state.selection.is_row_selected(5));
+        assert_eq!(state.pinned_rows, [5_000_500], "Found near where it was");
+
+        // All rows move far:
+        rows.reversed = true;
+        state.refresh_row_ids();
+        update(&rows, &mut state);
+        assert!(
+            state.selection.is_row_selected(9_999_994),
+            "Found with source_row_of_id"
+        );
+        assert!(state.pinned_rows.is_empty(), "Too far away to search for");
     }
 
-    fn right_top_ui(&mut self, ui: &mut Ui) {
-        let scroll_offset = vec2(ui.clip_rect().min.x - ui.min_rect().min.x, 0.0);
-        self.header_ui(ui, scroll_offset);
-    }
+    #[test]
+    fn test_selection_text() {
+        struct Cells;
 
-    fn left_bottom_ui(&mut self, ui: &mut Ui) {
-        self.region_ui(
-            ui,
-            vec2(0.0, ui.clip_rect().min.y - ui.min_rect().min.y),
-            false,
-        );
-    }
+        impl TableDelegate for Cells {
+            fn header_cell_ui(&mut self, _ui: &mut egui::Ui, _cell: &HeaderCellInfo) {}
 
-    fn finish(&mut self, ui: &mut Ui) {
-        // Paint column resize lines
+            fn cell_ui(&mut self, _ui: &mut egui::Ui, _cell: &CellInfo) {}
 
-        for (col_nr, ColumnResizer { scroll_offset, top }) in &self.visible_column_lines {
-            let col_nr = *col_nr;
-            let Some(column) = self.table.columns.get(col_nr) else {
-                continue;
-            };
-            if !column.resizable {
-                continue;
+            fn cell_text(&self, source_row: u64, col_nr: usize) -> Option<String> {
+                Some(format!("{source_row}:{col_nr}"))
             }
 
-            let column_id = column.id_for(col_nr);
-            let used_width = column.range.clamp(self.max_column_widths[col_nr]);
-
-            let column_width = self
-                .state
-                .col_widths
-                .entry(column_id)
-                .or_insert(column.current);
+            fn header_cell_text(&self, cell: &HeaderCellInfo) -> Option<String> {
+                (cell.row_nr == 0).then(|| format!("Group {}", cell.group_index))
+            }
+        }
 
-            let layout_width = *column_width; // Width used when computing col_x
+        let id = egui::Id::new("table");
+        let mut reversed = RowMapping::identity(10);
+        reversed.reverse();
+        let table = Table::new()
+            .num_rows(10)
+            .row_mapping(reversed)
+            .columns(vec![Column::new(100.0); 4])
+            .headers([
+                HeaderRow {
+                    height: 20.0,
+                    groups: vec![0..1, 1..3],
+                },
+                HeaderRow::new(20.0),
+            ]);
+        let display_columns = [0, 2, 1, 3];
+        let mut state = TableState {
+            visible_rows: 2..4,
+            ..Default::default()
+        };
+        assert_eq!(
+            table
+                .selection_text(id, &state, &Cells, &display_columns)
+                .as_deref(),
+            Some(
+                "Group 0\tGroup 1\t\t\n\
+                7:0\t7:2\t7:1\t7:3\n\
+                6:0\t6:2\t6:1\t6:3\n"
+            ),
+            "Without a selection, the rows in view are copied"
+        );
 
-            if ui.is_sizing_pass() || column.auto_size_this_frame {
-                // Shrink to fit the widest element in the column:
-                *column_width = used_width;
-            } else {
-                // Grow to fit the widest element in the column:
-                *column_width = column_width.max(used_width);
-            }
+        state.selection.cells = Some(CellSelection {
+            anchor: CellCoord {
+                row_nr: 5,
+                col_pos: 2,
+            },
+            focus: CellCoord {
+                row_nr: 4,
+                col_pos: 3,
+            },
+        });
+        assert_eq!(
+            table
+                .selection_text(id, &state, &Cells, &display_columns)
+                .as_deref(),
+            Some("Group 1\t\n5:1\t5:3\n4:1\t4:3\n")
+        );
 
-            let column_resize_id = self.id.with(column.id_for(col_nr)).with("resize");
+        let huge_table = Table::new()
+            .num_rows(10_000_000)
+            .columns(vec![Column::new(100.0); 4]);
+        state.selection.cells = None;
+        state.selection.rows.insert_range(0..huge_table.num_rows);
+        let text = huge_table
+            .selection_text(id, &state, &Cells, &display_columns)
+            .unwrap_or_default();
+        assert_eq!(
+            text.lines().count() as u64,
+            1 + MAX_ROWS_TO_COPY,
+            "Huge selections are cut short"
+        );
 
-            // Right side of the column, adjusted for any width change since layout:
-            let mut x = self.col_x[col_nr + 1] - scroll_offset.x + (*column_width - layout_width);
-            let yrange = Rangef::new(*top, ui.clip_rect().bottom());
-            let line_rect = egui::Rect::from_x_y_ranges(x..=x, yrange)
-                .expand(ui.style().interaction.resize_grab_radius_side);
+        assert_eq!(
+            table.selection_text(id, &state, &Delegate, &display_columns),
+            None,
+            "Nothing to copy"
+        );
+    }
 
-            let resize_response =
-                ui.interact(line_rect, column_resize_id, egui::Sense::click_and_drag());
+    #[test]
+    fn test_export() {
+        let ctx = egui::Context::default();
+        let mut delegate = TestDelegate::default();
+        let mut csv = String::new();
+        run_frame(&ctx, vec![], |ui| {
+            let table = || {
+                let mut reversed = RowMapping::identity(2_500);
+                reversed.reverse();
+                Table::new()
+                    .id_salt("table")
+                    .num_rows(2_500)
+                    .row_mapping(reversed)
+                    .columns(vec![Column::new(100.0); 3])
+            };
 
-            if resize_response.dragged()
-                && let Some(pointer) = ui.ctx().pointer_latest_pos()
-            {
-                // Drag-to-resize.
-                // TODO: use `ui.intrinsic_size` (once it exist) to prevent
-                // resizing below what the content can fit within.
-                let new_width = *column_width + pointer.x - x;
-                let new_width = column.range.clamp(new_width);
-                x += new_width - *column_width;
-                *column_width = new_width;
-            }
+            let mut state = TableState {
+                col_order: vec![egui::Id::new(2), egui::Id::new(1), egui::Id::new(0)],
+                ..Default::default()
+            };
+            state.set_column_visible(egui::Id::new(1), false);
+            state.store(ui.ctx(), table().get_id(ui));
 
-            let dragging_something_else =
-                ui.input(|i| i.pointer.any_down() || i.pointer.any_pressed());
-            let resize_hover = resize_response.hovered() && !dragging_something_else;
+            let bytes = table()
+                .export(ui, vec![], TextFormat::Csv, &mut delegate)
+                .expect("Writing to a Vec");
+            csv = String::from_utf8(bytes).expect("UTF-8");
+        });
 
-            if resize_hover || resize_response.dragged() {
-                ui.ctx().set_cursor_icon(egui::CursorIcon::ResizeColumn);
-            }
+        let chunks: Vec<Range<u64>> = (delegate.prepared.iter())
+            .map(|info| info.visible_rows.clone())
+            .collect();
+        assert_eq!(chunks, [0..1_000, 1_000..2_000, 2_000..2_500]);
+        assert_eq!(delegate.prepared[0].column_order, [2, 0]);
+        let lines: Vec<&str> = csv.lines().collect();
+        assert_eq!(lines.len(), 2_501);
+        assert_eq!(
+            lines[..3],
+            ["Column 2,Column 0", "24992,24990", "24982,24980"],
+            "Without header texts, the column names are used"
+        );
+        assert_eq!(lines[2_500], "02,00");
+    }
 
-            let stroke = if resize_response.dragged() {
-                ui.style().visuals.widgets.active.bg_stroke
-            } else if resize_hover {
-                ui.style().visuals.widgets.hovered.bg_stroke
-            } else {
-                // ui.visuals().widgets.inactive.bg_stroke
-                ui.visuals().widgets.noninteractive.bg_stroke
+    #[test]
+    fn test_cell_editing() {
+        let ctx = egui::Context::default();
+        ctx.style_mut(|style| style.scroll_animation = egui::style::ScrollAnimation::none());
+        let mut numbers = TestDelegate::default();
+        let run =
+            |numbers: &mut TestDelegate, events: Vec<egui::Event>, scroll_to_row: Option<u64>| {
+                let table = || {
+                    let table = Table::new()
+                        .id_salt("table")
+                        .num_rows(1_000)
+                        .columns(vec![Column::new(100.0).editable(true); 3]);
+                    match scroll_to_row {
+                        Some(row_nr) => table.scroll_to_row(row_nr, None),
+                        None => table,
+                    }
+                };
+                show_table(&ctx, events, table, numbers)
             };
 
-            ui.painter().vline(x, yrange, stroke);
-        }
+        // Focus a cell:
+        let (id, mut state) = run(&mut numbers, vec![], None);
+        state.focus = Some(CellCoord {
+            row_nr: 2,
+            col_pos: 1,
+        });
+        state.store(&ctx, id);
+        ctx.memory_mut(|mem| mem.request_focus(id));
+        run(&mut numbers, vec![], None);
+
+        // Typing starts editing, replacing the value:
+        let cell = |col_pos| Some(CellCoord { row_nr: 2, col_pos });
+        assert_eq!(
+            run(&mut numbers, vec![egui::Event::Text("4".into())], None)
+                .1
+                .editing_cell(),
+            cell(1)
+        );
+        run(&mut numbers, vec![egui::Event::Text("x".into())], None);
+
+        let (_, state) = run(&mut numbers, vec![key(egui::Key::Enter)], None);
+        let edit = state.editing.expect("Still editing");
+        assert_eq!(edit.value, "4x");
+        assert_eq!(edit.error.as_deref(), Some("\"4x\" is not a number"));
+
+        // The editor takes back the keyboard focus:
+        run(&mut numbers, vec![], None);
+        run(&mut numbers, vec![key(egui::Key::Backspace)], None);
+
+        // Tab commits and moves on to the next cell:
+        let (_, state) = run(&mut numbers, vec![key(egui::Key::Tab)], None);
+        assert_eq!(numbers.edited.get(&(2, 1)).map(String::as_str), Some("4"));
+        assert_eq!(state.focus, cell(2));
+        assert_eq!(state.editing_cell(), cell(2));
+        assert_eq!(state.editing.expect("Editing").value, "22");
+
+        let (_, state) = run(&mut numbers, vec![key(egui::Key::Escape)], None);
+        assert_eq!(state.editing_cell(), None);
+        assert!(!numbers.edited.contains_key(&(2, 2)), "Escape cancels");
+
+        assert_eq!(
+            run(&mut numbers, vec![key(egui::Key::F2)], None)
+                .1
+                .editing_cell(),
+            cell(2)
+        );
+
+        // The editor keeps the keyboard focus when scrolled out of view:
+        run(&mut numbers, vec![], None);
+        run(&mut numbers, vec![], Some(900));
+        run(&mut numbers, vec![], None);
+        let (_, state) = run(&mut numbers, vec![], None);
+        assert!(!state.visible_rows.contains(&2));
+        run(&mut numbers, vec![egui::Event::Text("7".into())], None);
+        run(&mut numbers, vec![key(egui::Key::Enter)], None);
+        assert_eq!(numbers.edited.get(&(2, 2)).map(String::as_str), Some("227"));
+
+        // Clicking away from an invalid value keeps it, without taking back the keyboard focus:
+        run(&mut numbers, vec![egui::Event::Text("x".into())], None);
+        run(&mut numbers, vec![], None);
+        let click_outside = |pressed| egui::Event::PointerButton {
+            pos: egui::pos2(800.0, 300.0),
+            button: egui::PointerButton::Primary,
+            pressed,
+            modifiers: egui::Modifiers::NONE,
+        };
+        run(&mut numbers, vec![click_outside(true)], None);
+        run(&mut numbers, vec![click_outside(false)], None);
+        let (_, state) = run(&mut numbers, vec![], None);
+        let edit = state.editing.expect("Still editing");
+        assert_eq!(edit.error.as_deref(), Some("\"x\" is not a number"));
+        run(&mut numbers, vec![], None);
+        assert_eq!(ctx.memory(|mem| mem.focused()), None);
     }
-}
 
-/// Returns the index of the first element that returns `true` using binary search.
-fn partition_point(range: RangeInclusive<u64>, second_partition: impl Fn(u64) -> bool) -> u64 {
-    let mut min = *range.start();
-    let mut max = *range.end();
+    #[test]
+    fn test_filter_row() {
+        let ctx = egui::Context::default();
+        let mut numbers = TestDelegate::default();
+        let run = |numbers: &mut TestDelegate, row_mapping: &Arc<RowMapping>, events| {
+            let table = || {
+                Table::new()
+                    .id_salt("table")
+                    .row_mapping(Arc::clone(row_mapping))
+                    .columns(vec![Column::new(100.0).resizable(false); 2])
+                    .headers([HeaderRow::new(20.0)])
+                    .filter_row(24.0)
+            };
+            show_table(&ctx, events, table, numbers)
+        };
+        let mut row_mapping = Arc::new(RowMapping::identity(100));
+
+        run(&mut numbers, &row_mapping, vec![]);
+        let (id, _) = run(&mut numbers, &row_mapping, vec![]);
+        let table_rect = ctx.read_response(id).expect("The table was shown").rect;
+        let body_top = (numbers.cells.iter())
+            .find(|(cell, _)| cell.row_nr == 0)
+            .map(|(_, rect)| rect.top());
+        assert_eq!(
+            body_top,
+            Some(table_rect.top() + 44.0),
+            "The body is below the filter row"
+        );
 
-    debug_assert!(min < max, "Bad call to partition_point");
+        // Click the filter of the first column, and type into it:
+        let pos = table_rect.left_top() + egui::vec2(50.0, 32.0);
+        let click = |pressed| egui::Event::PointerButton {
+            pos,
+            button: egui::PointerButton::Primary,
+            pressed,
+            modifiers: egui::Modifiers::NONE,
+        };
+        run(
+            &mut numbers,
+            &row_mapping,
+            vec![egui::Event::PointerMoved(pos), click(true), click(false)],
+        );
+        let (_, state) = run(
+            &mut numbers,
+            &row_mapping,
+            vec![egui::Event::Text("2".into())],
+        );
+        let column_id = egui::Id::new(0_usize);
+        assert_eq!(
+            state.filters.get(column_id),
+            Some(&FilterValue::Text("2".to_owned()))
+        );
+        assert_eq!(numbers.filter_changes, [state.filters]);
+        row_mapping = Arc::new(RowMapping::filtered(100, |row| {
+            numbers.filter_changes[0].matches(column_id, &row.to_string())
+        }));
+        assert_eq!(row_mapping.len(), 19, "2, 12, 20..=29, 32, 42, …");
+
+        run(&mut numbers, &row_mapping, vec![]);
+        assert!(numbers.filter_changes.is_empty());
+    }
 
-    while min < max {
-        let mid = min + (max - min) / 2;
+    #[test]
+    fn test_cell_spans() {
+        let ctx = egui::Context::default();
+        let mut delegate = TestDelegate {
+            col_span: Some(1..3),
+            ..Default::default()
+        };
+        let mut run = |num_sticky_cols| {
+            let table = || {
+                Table::new()
+                    .id_salt("table")
+                    .num_rows(10)
+                    .columns(vec![Column::new(100.0).resizable(false); 4])
+                    .num_sticky_cols(num_sticky_cols)
+            };
+            show_table(&ctx, vec![], table, &mut delegate);
+            show_table(&ctx, vec![], table, &mut delegate);
+
+            // The columns and widths of the cells in row 3:
+            let mut cells: Vec<(Range<usize>, f32)> = (delegate.cells.iter())
+                .filter(|(cell, _)| cell.row_nr == 3)
+                .map(|(cell, rect)| (cell.col_pos_range.clone(), rect.width()))
+                .collect();
+            cells.sort_by_key(|(col_pos_range, _)| col_pos_range.start);
+            // The scrolled columns also show the sticky column next to them, hidden under it:
+            cells.dedup();
+            cells
+        };
 
-        if second_partition(mid) {
-            max = mid;
-        } else {
-            min = mid + 1;
-        }
+        assert_eq!(
+            run(0),
+            [(0..1, 100.0), (1..3, 200.0), (3..4, 100.0)],
+            "One cell for the span"
+        );
+        assert_eq!(
+            run(2),
+            [(0..1, 100.0), (1..2, 100.0), (2..3, 100.0), (3..4, 100.0)],
+            "The span is cut at the sticky columns"
+        );
     }
 
-    min
-}
+    #[test]
+    fn test_row_spans() {
+        let ctx = egui::Context::default();
+        ctx.style_mut(|style| style.scroll_animation = egui::style::ScrollAnimation::none());
+        let mut delegate = TestDelegate {
+            row_span: Some(2..6),
+            ..Default::default()
+        };
+        let run = |delegate: &mut TestDelegate, scroll_to_row: Option<u64>| {
+            let table = || {
+                let table = Table::new()
+                    .id_salt("table")
+                    .num_rows(1_000)
+                    .columns(vec![Column::new(100.0); 2])
+                    .show_pinned_rows_in_body(false);
+                match scroll_to_row {
+                    Some(row_nr) => table.scroll_to_row(row_nr, Some(egui::Align::TOP)),
+                    None => table,
+                }
+            };
+            show_table(&ctx, vec![], table, delegate)
+        };
+        // The heights of the shown merged cells:
+        let merged_cells = |delegate: &TestDelegate| -> Vec<f32> {
+            (delegate.cells.iter())
+                .filter(|(cell, _)| cell.col_nr == 0 && cell.row_range == (2..6))
+                .map(|(_, rect)| rect.height())
+                .collect()
+        };
 
-#[cfg(test)]
-mod tests {
-    use crate::table::partition_point;
+        run(&mut delegate, None);
+        let (id, mut state) = run(&mut delegate, None);
+        assert_eq!(merged_cells(&delegate), [80.0]);
+
+        // Still shown when the top row is pinned, and left out of the body:
+        state.pin_row(2);
+        state.store(&ctx, id);
+        run(&mut delegate, None);
+        assert_eq!(merged_cells(&delegate), [60.0], "Without the hidden row 2");
+        let pinned_rows: Vec<(u64, u64)> = (delegate.cells.iter())
+            .filter(|(cell, _)| cell.col_nr == 0 && cell.is_pinned)
+            .map(|(cell, _)| (cell.row_range.start, cell.row_range.end))
+            .collect();
+        assert_eq!(
+            pinned_rows,
+            [(2, 3)],
+            "Spans are ignored for the pinned rows"
+        );
+
+        // Still shown when the top rows are scrolled out of view:
+        let (_, mut state) = run(&mut delegate, None);
+        state.pinned_rows.clear();
+        state.store(&ctx, id);
+        run(&mut delegate, Some(4));
+        run(&mut delegate, None);
+        let (_, state) = run(&mut delegate, None);
+        assert!(!state.visible_rows.contains(&2));
+        assert_eq!(merged_cells(&delegate), [80.0]);
+    }
 
     #[test]
     fn test_partition_point() {
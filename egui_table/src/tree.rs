@@ -0,0 +1,145 @@
+//! Showing your rows as a tree, with expandable parent rows.
+//!
+//! See [`crate::Table::tree_column`] and [`crate::TableDelegate::tree_node`].
+
+use std::sync::Arc;
+
+use egui::{Sense, Ui, Vec2};
+
+use crate::RowMapping;
+
+/// Where a row of your data source is in the tree.
+///
+/// The rows of your data source must be in depth-first order,
+/// i.e. each parent row is directly followed by all of its descendants.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct TreeNode {
+    /// How deep in the tree the row is, with 0 for the root rows.
+    pub depth: usize,
+
+    /// The number of children, grandchildren etc. of this row.
+    ///
+    /// These are the rows directly after this one.
+    pub num_descendants: u64,
+}
+
+impl TreeNode {
+    /// A row without any children.
+    #[inline]
+    pub fn leaf(depth: usize) -> Self {
+        Self {
+            depth,
+            num_descendants: 0,
+        }
+    }
+
+    #[inline]
+    pub fn has_children(&self) -> bool {
+        0 < self.num_descendants
+    }
+}
+
+/// The visual rows of a tree, cached in [`crate::TableState`] between frames.
+#[derive(Clone, Debug)]
+pub(crate) struct FlatTree {
+    /// The [`crate::Table::num_rows`] it was made for.
+    pub num_source_rows: u64,
+
+    /// The [`crate::TableState`] tree generation it was made for.
+    pub generation: u64,
+
+    pub row_mapping: Arc<RowMapping>,
+}
+
+/// Only the source rows whose ancestors are all expanded.
+///
+/// This skips over collapsed subtrees, so it only visits the rows that end up visible.
+pub(crate) fn flatten(
+    num_source_rows: u64,
+    is_expanded: impl Fn(u64) -> bool,
+    tree_node: impl Fn(u64) -> TreeNode,
+) -> RowMapping {
+    let mut source_rows = vec![];
+    let mut source_row = 0;
+    while source_row < num_source_rows {
+        source_rows.push(source_row);
+        let node = tree_node(source_row);
+        source_row += 1;
+        if node.has_children() && !is_expanded(source_row - 1) {
+            source_row = source_row.saturating_add(node.num_descendants);
+        }
+    }
+    RowMapping::new(source_rows)
+}
+
+/// Indent according to the depth of the row, then show a triangle for expanding and collapsing it.
+///
+/// Leaf rows get the same space, but no triangle, so that they line up with their siblings.
+/// Returns `true` if the triangle was clicked.
+///
+/// This is the default implementation of [`crate::TableDelegate::tree_disclosure_ui`].
+pub fn disclosure_ui(ui: &mut Ui, node: TreeNode, is_expanded: bool) -> bool {
+    ui.add_space(node.depth as f32 * ui.spacing().indent);
+
+    let size = Vec2::splat(ui.spacing().icon_width);
+    if node.has_children() {
+        let (_, response) = ui.allocate_exact_size(size, Sense::click());
+        let openness = ui.ctx().animate_bool(response.id, is_expanded);
+        egui::collapsing_header::paint_default_icon(ui, openness, &response);
+        response.clicked()
+    } else {
+        ui.allocate_exact_size(size, Sense::hover());
+        false
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::BTreeSet;
+
+    use super::*;
+
+    #[test]
+    fn test_flatten() {
+        // 0
+        // ├ 1
+        // │ └ 2
+        // └ 3
+        // 4
+        // └ 5
+        let nodes = [
+            TreeNode {
+                depth: 0,
+                num_descendants: 3,
+            },
+            TreeNode {
+                depth: 1,
+                num_descendants: 1,
+            },
+            TreeNode::leaf(2),
+            TreeNode::leaf(1),
+            TreeNode {
+                depth: 0,
+                num_descendants: 1,
+            },
+            TreeNode::leaf(1),
+        ];
+        let flatten_with = |expanded: &[u64]| {
+            let expanded: BTreeSet<u64> = expanded.iter().copied().collect();
+            flatten(
+                nodes.len() as u64,
+                |row| expanded.contains(&row),
+                |row| nodes[usize::try_from(row).expect("Small row")],
+            )
+        };
+
+        assert_eq!(flatten_with(&[]).as_slice(), [0, 4]);
+        assert_eq!(flatten_with(&[0]).as_slice(), [0, 1, 3, 4]);
+        assert_eq!(flatten_with(&[0, 1, 4]).as_slice(), [0, 1, 2, 3, 4, 5]);
+        assert_eq!(
+            flatten_with(&[1, 4]).as_slice(),
+            [0, 4, 5],
+            "Children of collapsed rows stay hidden"
+        );
+    }
+}